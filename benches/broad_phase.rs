@@ -0,0 +1,70 @@
+//! Compares the brute force and grid broad phases over the same scenes.
+//!
+//! Run with `cargo bench --bench broad_phase`.
+
+use circles::{BroadPhase, Circle, ContainerShape, SimConfig, World};
+use glam::DVec2;
+use rand::Rng;
+use std::time::Instant;
+
+const COUNTS: [usize; 3] = [1_000, 5_000, 20_000];
+const TICKS: u32 = 10;
+
+fn main() {
+    for count in COUNTS {
+        let circles = scene(count);
+
+        let (brute_force, brute_force_positions) = run(&circles, BroadPhase::BruteForce);
+        let (grid, grid_positions) = run(&circles, BroadPhase::Grid);
+
+        let max_error = brute_force_positions
+            .iter()
+            .zip(&grid_positions)
+            .map(|(a, b)| a.distance(*b))
+            .fold(0., f64::max);
+
+        println!(
+            "{count:>6} circles: brute force {:>9.3} ms/tick, grid {:>7.3} ms/tick, max difference {max_error:e}",
+            brute_force * 1000.,
+            grid * 1000.,
+        );
+    }
+}
+
+/// `count` non-overlapping circles of random sizes on a square lattice filling a
+/// disc of radius 300.
+fn scene(count: usize) -> Vec<(DVec2, f64)> {
+    let mut rng = rand::thread_rng();
+    let spacing = (std::f64::consts::PI * 300. * 300. / count as f64).sqrt() * 0.95;
+    let cells = (300. / spacing) as i32 + 1;
+    (-cells..=cells)
+        .flat_map(|y| (-cells..=cells).map(move |x| DVec2::new(x as f64, y as f64) * spacing))
+        .filter(|position| position.length() < 300.)
+        .take(count)
+        .map(|position| (position, spacing * rng.gen_range(0.3..0.5)))
+        .collect()
+}
+
+fn run(circles: &[(DVec2, f64)], broad_phase: BroadPhase) -> (f64, Vec<DVec2>) {
+    let config = SimConfig {
+        container: ContainerShape::Disc { radius: 320. },
+        ..SimConfig::default()
+    };
+    let centre = config.centre();
+    let mut world = World::new(config);
+    world.set_broad_phase(broad_phase);
+    for &(position, radius) in circles {
+        world.add(Circle::new(centre + position, radius, (255, 255, 255)));
+    }
+
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        world.step();
+    }
+    let seconds = start.elapsed().as_secs_f64() / TICKS as f64;
+
+    (
+        seconds,
+        world.circles().iter().map(Circle::position).collect(),
+    )
+}
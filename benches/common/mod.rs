@@ -0,0 +1,40 @@
+use circles::{Circle, ContainerShape, SimConfig, World};
+use glam::DVec2;
+use std::time::Instant;
+
+/// The radius of every circle in `floor_pile`.
+pub const RADIUS: f64 = 3.;
+
+/// Stacks `count` circles in a hexagonal lattice on the floor of a wide box,
+/// and runs `settle_ticks` of the world that `config` describes with that box.
+pub fn floor_pile(config: SimConfig, count: usize, settle_ticks: u32) -> World {
+    let config = SimConfig {
+        container: ContainerShape::Rect {
+            width: 760.,
+            height: 700.,
+        },
+        ..config
+    };
+    let corner = config.centre() + DVec2::new(-380. + RADIUS, 350. - RADIUS);
+    let mut world = World::new(config);
+    let columns = (760. / (RADIUS * 2.)) as usize;
+    for i in 0..count {
+        let (row, column) = (i / columns, i % columns);
+        let offset =
+            DVec2::new((column * 2 + row % 2) as f64, -(row as f64) * 3f64.sqrt()) * RADIUS;
+        world.add(Circle::new(corner + offset, RADIUS, (255, 255, 255)));
+    }
+    for _ in 0..settle_ticks {
+        world.step();
+    }
+    world
+}
+
+/// The mean time taken by each of `ticks` steps of `world`, in seconds.
+pub fn time_ticks(world: &mut World, ticks: u32) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ticks {
+        world.step();
+    }
+    start.elapsed().as_secs_f64() / ticks as f64
+}
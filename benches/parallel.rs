@@ -0,0 +1,36 @@
+//! Compares the serial and parallel contact solvers on an awake pile.
+//!
+//! Run with `cargo bench --bench parallel --features parallel`.
+
+mod common;
+
+use circles::SimConfig;
+use common::{floor_pile, time_ticks};
+
+const COUNTS: [usize; 3] = [1_000, 2_500, 5_000];
+const SETTLE_TICKS: u32 = 200;
+const TICKS: u32 = 100;
+
+fn main() {
+    println!("{} threads", rayon::current_num_threads());
+    for count in COUNTS {
+        let serial = run(count, false);
+        let parallel = run(count, true);
+        println!(
+            "{count:>5} circles: serial {:>7.3} ms/tick, parallel {:>7.3} ms/tick",
+            serial * 1000.,
+            parallel * 1000.,
+        );
+    }
+}
+
+/// Times a tick of a pile that has started to settle, with sleeping turned off.
+fn run(count: usize, parallel: bool) -> f64 {
+    let config = SimConfig {
+        sleep_speed: 0.,
+        parallel,
+        ..SimConfig::default()
+    };
+    let mut world = floor_pile(config, count, SETTLE_TICKS);
+    time_ticks(&mut world, TICKS)
+}
@@ -0,0 +1,41 @@
+//! Compares the cost of a settled pile with and without sleeping.
+//!
+//! Run with `cargo bench --bench sleep`.
+
+mod common;
+
+use circles::SimConfig;
+use common::{floor_pile, time_ticks};
+
+const COUNTS: [usize; 3] = [1_000, 2_500, 5_000];
+const SETTLE_TICKS: u32 = 1_500;
+const TICKS: u32 = 100;
+
+fn main() {
+    for count in COUNTS {
+        let (awake, _) = run(count, 0.);
+        let (sleeping, asleep) = run(count, SimConfig::default().sleep_speed);
+        println!(
+            "{count:>5} circles: awake {:>7.3} ms/tick, sleeping {:>7.3} ms/tick with {asleep} asleep",
+            awake * 1000.,
+            sleeping * 1000.,
+        );
+    }
+}
+
+/// Times a tick of a settled pile. Returns the time and how many circles were
+/// asleep.
+fn run(count: usize, sleep_speed: f64) -> (f64, usize) {
+    let config = SimConfig {
+        sleep_speed,
+        ..SimConfig::default()
+    };
+    let mut world = floor_pile(config, count, SETTLE_TICKS);
+    let seconds = time_ticks(&mut world, TICKS);
+    let asleep = world
+        .circles()
+        .iter()
+        .filter(|circle| circle.asleep())
+        .count();
+    (seconds, asleep)
+}
@@ -0,0 +1,37 @@
+//! Replays a recorded session without a window.
+//!
+//! `cargo run --no-default-features --example replay -- <recording> [snapshot]`
+//!
+//! Prints a summary of the final state, and saves it as a snapshot if a second
+//! path is given.
+
+use circles::Recording;
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let path = args.next().ok_or("usage: replay <recording> [snapshot]")?;
+
+    let recording = Recording::load(BufReader::new(File::open(path)?))?;
+    let state = recording.replay();
+
+    let duration: f64 = recording.frames().iter().map(|frame| frame.dt).sum();
+    println!(
+        "replayed {} frames ({duration:.2}s), seed {}, {} circles",
+        recording.frames().len(),
+        state.seed(),
+        state.world().circles().len(),
+    );
+
+    if let Some(output) = args.next() {
+        state.save(BufWriter::new(File::create(&output)?))?;
+        println!("saved final state to {output}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::{env, path::PathBuf};
+
+/// Command line options for the frontend.
+pub struct Args {
+    pub seed: Option<u64>,
+    pub load: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub overrides: Vec<String>,
+    pub bindings: Option<PathBuf>,
+    /// Draw every circle with meshes of its own, to compare frame times.
+    pub unbatched: bool,
+    /// Play the replay back without vsync, then print how long frames took to
+    /// draw and exit.
+    pub timing: bool,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        let mut args = Self {
+            seed: None,
+            load: None,
+            record: None,
+            replay: None,
+            config: None,
+            overrides: Vec::new(),
+            bindings: None,
+            unbatched: false,
+            timing: false,
+        };
+
+        let mut iter = env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = iter.next().ok_or("--seed requires a value")?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("invalid seed '{value}'"))?;
+                    args.seed = Some(seed);
+                }
+                "--load" => {
+                    let value = iter.next().ok_or("--load requires a file")?;
+                    args.load = Some(value.into());
+                }
+                "--record" => {
+                    let value = iter.next().ok_or("--record requires a file")?;
+                    args.record = Some(value.into());
+                }
+                "--replay" => {
+                    let value = iter.next().ok_or("--replay requires a file")?;
+                    args.replay = Some(value.into());
+                }
+                "--config" => {
+                    let value = iter.next().ok_or("--config requires a file")?;
+                    args.config = Some(value.into());
+                }
+                "--set" => {
+                    let value = iter.next().ok_or("--set requires key=value")?;
+                    args.overrides.push(value);
+                }
+                "--bindings" => {
+                    let value = iter.next().ok_or("--bindings requires a file")?;
+                    args.bindings = Some(value.into());
+                }
+                "--unbatched" => args.unbatched = true,
+                "--timing" => args.timing = true,
+                _ => return Err(format!("unknown argument '{arg}'")),
+            }
+        }
+
+        if args.timing && args.replay.is_none() {
+            return Err("--timing requires --replay".to_string());
+        }
+
+        Ok(args)
+    }
+}
@@ -0,0 +1,252 @@
+use crate::input::Input;
+use enum_map::EnumMap;
+use serde::de::{value, Deserialize, IntoDeserializer};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+    str::FromStr,
+};
+
+/// Modifier keys held as part of a chord.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    /// Whether every modifier in `self` is also in `held`.
+    pub fn within(self, held: Modifiers) -> bool {
+        (!self.ctrl || held.ctrl) && (!self.shift || held.shift) && (!self.alt || held.alt)
+    }
+
+    fn count(self) -> usize {
+        [self.ctrl, self.shift, self.alt]
+            .into_iter()
+            .filter(|&held| held)
+            .count()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Button {
+    /// A key by the name of its ggez `KeyCode`, such as `Space` or `LBracket`.
+    /// The frontend checks that the name exists.
+    Key(String),
+    Mouse(MouseButton),
+}
+
+/// A key or mouse button, along with the modifiers that must be held with it,
+/// written like `Ctrl+Shift+Z` or `MouseLeft`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub button: Button,
+}
+
+/// Which keys and mouse buttons press each input.
+///
+/// An input can have any number of chords. Holding extra modifiers does not
+/// stop a chord, so shift-click still clicks, but where several chords share a
+/// button only those using the most of the held modifiers count, so Ctrl+S does
+/// not also press S.
+pub struct Bindings {
+    chords: EnumMap<Input, Vec<Chord>>,
+}
+
+impl Bindings {
+    /// Reads a TOML table of input names to lists of chords, such as
+    /// `Clear = ["Space", "Ctrl+Back"]`. Inputs left out keep their default
+    /// chords, and an empty list unbinds one.
+    pub fn from_toml(source: &str) -> Result<Self, BindingsError> {
+        let table: BTreeMap<String, Vec<String>> = toml::from_str(source)?;
+        let mut bindings = Self::default();
+        for (name, chords) in table {
+            let input = Input::deserialize(name.as_str().into_deserializer())
+                .map_err(|_: value::Error| BindingsError::UnknownInput(name))?;
+            bindings.chords[input] = chords
+                .iter()
+                .map(|chord| chord.parse())
+                .collect::<Result<_, _>>()?;
+        }
+        bindings.check()?;
+        Ok(bindings)
+    }
+
+    pub fn chords(&self, input: Input) -> &[Chord] {
+        &self.chords[input]
+    }
+
+    /// Every chord along with the input it presses.
+    pub fn iter(&self) -> impl Iterator<Item = (Input, &Chord)> {
+        self.chords
+            .iter()
+            .flat_map(|(input, chords)| chords.iter().map(move |chord| (input, chord)))
+    }
+
+    /// The inputs pressed while the buttons for which `down` is true and the
+    /// `held` modifiers are held.
+    pub fn pressed(&self, down: impl Fn(&Button) -> bool, held: Modifiers) -> Vec<Input> {
+        let matching: Vec<_> = self
+            .iter()
+            .filter(|(_, chord)| chord.modifiers.within(held) && down(&chord.button))
+            .collect();
+        matching
+            .iter()
+            .filter(|(_, chord)| {
+                !matching.iter().any(|(_, other)| {
+                    other.button == chord.button
+                        && other.modifiers.count() > chord.modifiers.count()
+                })
+            })
+            .map(|&(input, _)| input)
+            .collect()
+    }
+
+    /// Fails if one chord presses two different inputs.
+    fn check(&self) -> Result<(), BindingsError> {
+        let mut seen = HashMap::new();
+        for (input, chord) in self.iter() {
+            match seen.insert(chord, input) {
+                Some(other) if other != input => {
+                    return Err(BindingsError::Conflict(chord.clone(), other, input));
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Input::*;
+
+        let mut chords = EnumMap::default();
+        let table: [(Input, &[&str]); 26] = [
+            (LeftMouse, &["MouseLeft"]),
+            (RightMouse, &["MouseRight"]),
+            (MiddleMouse, &["MouseMiddle"]),
+            (Link, &["LShift", "RShift"]),
+            (Heavy, &["LControl", "RControl"]),
+            (Hose, &["H"]),
+            (NextLinkTool, &["K"]),
+            (NextDrag, &["D"]),
+            (FewerIterations, &["LBracket"]),
+            (MoreIterations, &["RBracket"]),
+            (Adaptive, &["A"]),
+            (Clear, &["Space"]),
+            (NextContainer, &["C"]),
+            (Emitter, &["X"]),
+            (Editor, &["E"]),
+            (Debug, &["F1"]),
+            (Pause, &["P"]),
+            (Step, &["Period"]),
+            (Slower, &["Minus"]),
+            (Faster, &["Equals"]),
+            (Reverse, &["R"]),
+            (ScrubBack, &["Left"]),
+            (ScrubForward, &["Right"]),
+            (Save, &["S"]),
+            (Load, &["L"]),
+            (Quit, &["Escape"]),
+        ];
+        for (input, names) in table {
+            chords[input] = names
+                .iter()
+                .map(|name| name.parse().expect("default chords are valid"))
+                .collect();
+        }
+        Self { chords }
+    }
+}
+
+impl FromStr for Chord {
+    type Err = BindingsError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let invalid = || BindingsError::InvalidChord(source.to_string());
+        let mut parts: Vec<_> = source.split('+').map(str::trim).collect();
+        let button = match parts.pop().ok_or_else(invalid)? {
+            "" => return Err(invalid()),
+            "MouseLeft" => Button::Mouse(MouseButton::Left),
+            "MouseRight" => Button::Mouse(MouseButton::Right),
+            "MouseMiddle" => Button::Mouse(MouseButton::Middle),
+            key => Button::Key(key.to_string()),
+        };
+        let mut modifiers = Modifiers::default();
+        for part in parts {
+            let modifier = match part {
+                "Ctrl" => &mut modifiers.ctrl,
+                "Shift" => &mut modifiers.shift,
+                "Alt" => &mut modifiers.alt,
+                _ => return Err(invalid()),
+            };
+            *modifier = true;
+        }
+        Ok(Self { modifiers, button })
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "Ctrl+"),
+            (self.modifiers.shift, "Shift+"),
+            (self.modifiers.alt, "Alt+"),
+        ];
+        for (held, name) in modifiers {
+            if held {
+                write!(f, "{name}")?;
+            }
+        }
+        match &self.button {
+            Button::Key(name) => write!(f, "{name}"),
+            Button::Mouse(button) => write!(f, "Mouse{button:?}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Parse(toml::de::Error),
+    UnknownInput(String),
+    InvalidChord(String),
+    /// A chord bound to two different inputs.
+    Conflict(Chord, Input, Input),
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "malformed bindings: {error}"),
+            Self::UnknownInput(name) => write!(f, "unknown input '{name}'"),
+            Self::InvalidChord(chord) => write!(f, "invalid chord '{chord}'"),
+            Self::Conflict(chord, a, b) => write!(f, "{chord} is bound to both {a:?} and {b:?}"),
+        }
+    }
+}
+
+impl Error for BindingsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<toml::de::Error> for BindingsError {
+    fn from(error: toml::de::Error) -> Self {
+        Self::Parse(error)
+    }
+}
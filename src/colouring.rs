@@ -0,0 +1,89 @@
+use crate::world::{pair_mut, Circle};
+use rayon::prelude::*;
+
+/// The fewest pairs of one colour worth handing to another thread.
+const MIN_BATCH: usize = 64;
+
+/// Contact pairs sorted into colours such that no circle is in two pairs of the
+/// same colour, so all the pairs of a colour can be solved at once.
+///
+/// Colours are solved in order, so the result depends only on the pairs given
+/// and the order they were added in, not on how the work is spread over
+/// threads.
+pub struct Colouring {
+    colours: Vec<Vec<(usize, usize)>>,
+    /// Pairs that found no free colour, solved one at a time after the rest.
+    overflow: Vec<(usize, usize)>,
+    /// The colours each circle has been given so far, one bit each.
+    used: Vec<u64>,
+}
+
+/// The circles being solved, shared between the threads solving one colour.
+struct Shared(*mut Circle);
+
+// each thread only touches the circles of its own pairs, which no other pair of
+// the same colour contains
+unsafe impl Sync for Shared {}
+
+impl Colouring {
+    pub fn new() -> Self {
+        Self {
+            colours: Vec::new(),
+            overflow: Vec::new(),
+            used: Vec::new(),
+        }
+    }
+
+    /// Starts again with no pairs among `count` circles.
+    pub fn reset(&mut self, count: usize) {
+        for colour in &mut self.colours {
+            colour.clear();
+        }
+        self.overflow.clear();
+        self.used.clear();
+        self.used.resize(count, 0);
+    }
+
+    /// Adds the pair of circles `i` and `j` with the first colour neither of them
+    /// has yet.
+    pub fn add(&mut self, i: usize, j: usize) {
+        assert_ne!(i, j);
+        let colour = (!(self.used[i] | self.used[j])).trailing_zeros() as usize;
+        if colour == u64::BITS as usize {
+            self.overflow.push((i, j));
+            return;
+        }
+        if colour == self.colours.len() {
+            self.colours.push(Vec::new());
+        }
+        self.colours[colour].push((i, j));
+        self.used[i] |= 1 << colour;
+        self.used[j] |= 1 << colour;
+    }
+
+    /// Calls `f` on both circles of every pair, returning the largest result.
+    pub fn solve(&self, circles: &mut [Circle], f: fn(&mut Circle, &mut Circle) -> f64) -> f64 {
+        assert_eq!(circles.len(), self.used.len());
+        let shared = Shared(circles.as_mut_ptr());
+        let shared = &shared;
+        let mut largest = 0_f64;
+        for colour in &self.colours {
+            let colour_largest = colour
+                .par_iter()
+                .with_min_len(MIN_BATCH)
+                .map(|&(i, j)| {
+                    // SAFETY: `add` checked that `i` and `j` differ and are in
+                    // bounds, and gives no circle two pairs of the same colour
+                    let (a, b) = unsafe { (&mut *shared.0.add(i), &mut *shared.0.add(j)) };
+                    f(a, b)
+                })
+                .reduce(|| 0., f64::max);
+            largest = largest.max(colour_largest);
+        }
+        for &(i, j) in &self.overflow {
+            let (a, b) = pair_mut(circles, i, j);
+            largest = largest.max(f(a, b));
+        }
+        largest
+    }
+}
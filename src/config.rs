@@ -0,0 +1,271 @@
+use crate::{
+    container::ContainerShape, emitter::Emitter, material::Material, obstacle::Obstacle,
+    world::Colour,
+};
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt};
+
+/// The most circles per second the hose or an emitter may spawn. Each spawns
+/// every circle at one point, so anything faster only tries to fit circles
+/// where there is no room, at a cost per attempt.
+pub(crate) const MAX_SPAWN_RATE: f64 = 1000.;
+
+/// The tunable parameters of a simulation.
+///
+/// Every field has a default, so a config file only needs the fields it changes.
+/// Snapshots hold the config, so changing its fields means bumping
+/// `snapshot::VERSION`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimConfig {
+    /// The size of the world, which is also the size of the window.
+    pub width: f64,
+    pub height: f64,
+    /// Ticks per second of simulated time.
+    pub tps: u32,
+    /// The most ticks run in one frame. Time beyond that is dropped, slowing
+    /// the simulation down rather than falling ever further behind.
+    pub max_ticks_per_frame: u32,
+    /// How many seconds of the past are kept for running time backwards.
+    pub history: f64,
+    pub gravity: f64,
+    /// The share of velocity lost per second, roughly, to drag proportional to
+    /// speed.
+    pub linear_drag: f64,
+    /// Drag proportional to the square of speed, per pixel travelled.
+    pub quadratic_drag: f64,
+    /// Circles moving slower than this, in pixels per second, for `sleep_ticks`
+    /// fall asleep along with everything touching them. 0 never sleeps.
+    pub sleep_speed: f64,
+    pub sleep_ticks: u32,
+    /// Collision solver iterations per step.
+    pub repetitions: u32,
+    /// Whether to split fast ticks into substeps.
+    pub adaptive: bool,
+    pub max_substeps: u32,
+    /// Whether to solve contacts on all cores, which needs the `parallel`
+    /// feature. The result is just as repeatable, but not the same as solving
+    /// them one at a time.
+    pub parallel: bool,
+    /// The furthest a circle may move in one substep, as a share of the
+    /// smallest radius.
+    pub substep_travel: f64,
+    pub smallest_radius: f64,
+    pub largest_radius: f64,
+    /// Circles per second spawned while the mouse is held in hose mode.
+    pub hose_rate: f64,
+    /// The velocity, in pixels per second, of circles from the hose.
+    pub hose_velocity: DVec2,
+    /// The density of circles spawned while holding control, where the usual
+    /// density is 1.
+    pub heavy_density: f64,
+    /// The material of circles spawned with the mouse.
+    pub material: Material,
+    /// The material of the container and obstacles.
+    pub wall_material: Material,
+    /// The container, which is centred in the world.
+    pub container: ContainerShape,
+    /// Fixed obstacles inside the container.
+    pub obstacles: Vec<Obstacle>,
+    pub emitters: Vec<Emitter>,
+    pub background: Colour,
+    pub outer_colour: Colour,
+    pub obstacle_colour: Colour,
+}
+
+impl SimConfig {
+    /// Parses a TOML config, then applies `overrides`, each a `key=value` pair in
+    /// TOML syntax, such as `gravity=250` or `background=[20, 20, 40]`.
+    pub fn from_toml(source: &str, overrides: &[String]) -> Result<Self, ConfigError> {
+        let mut table: toml::value::Table = toml::from_str(source)?;
+        for entry in overrides {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| ConfigError::Override(entry.clone()))?;
+            let value: toml::Value = toml::from_str(&format!("value = {}", value.trim()))
+                .ok()
+                .and_then(|mut parsed: toml::value::Table| parsed.remove("value"))
+                .ok_or_else(|| ConfigError::Override(entry.clone()))?;
+            table.insert(key.trim().to_string(), value);
+        }
+
+        let config: Self = toml::Value::Table(table).try_into()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: &str| Err(ConfigError::Invalid(reason.to_string()));
+
+        let finite = [
+            self.width,
+            self.height,
+            self.history,
+            self.gravity,
+            self.linear_drag,
+            self.quadratic_drag,
+            self.sleep_speed,
+            self.substep_travel,
+            self.smallest_radius,
+            self.largest_radius,
+            self.hose_rate,
+            self.hose_velocity.x,
+            self.hose_velocity.y,
+            self.heavy_density,
+            self.container.extent().x,
+            self.container.extent().y,
+        ];
+        if finite.iter().any(|value| !value.is_finite()) {
+            return invalid("values must be finite");
+        }
+        if self.width <= 0. || self.height <= 0. {
+            return invalid("width and height must be positive");
+        }
+        if self.linear_drag < 0. || self.quadratic_drag < 0. {
+            return invalid("drag must not be negative");
+        }
+        if self.sleep_speed < 0. {
+            return invalid("sleep speed must not be negative");
+        }
+        if self.sleep_ticks == 0 {
+            return invalid("sleep ticks must be at least 1");
+        }
+        if self.tps == 0 {
+            return invalid("tps must be positive");
+        }
+        if self.history < 0. {
+            return invalid("history must not be negative");
+        }
+        if self.max_ticks_per_frame == 0 {
+            return invalid("max ticks per frame must be at least 1");
+        }
+        if self.repetitions == 0 {
+            return invalid("repetitions must be at least 1");
+        }
+        if self.max_substeps == 0 {
+            return invalid("max substeps must be at least 1");
+        }
+        if self.parallel && !cfg!(feature = "parallel") {
+            return invalid("the parallel solver needs the parallel feature");
+        }
+        if self.substep_travel <= 0. {
+            return invalid("substep travel must be positive");
+        }
+        if !(self.hose_rate > 0. && self.hose_rate <= MAX_SPAWN_RATE) {
+            return invalid(&format!(
+                "hose rate must be positive and at most {MAX_SPAWN_RATE}"
+            ));
+        }
+        if self.hose_velocity.length() > self.max_spawn_speed() {
+            return invalid("hose velocity must be at most the world size per tick");
+        }
+        if self.heavy_density <= 0. {
+            return invalid("heavy density must be positive");
+        }
+        if self.smallest_radius <= 0. {
+            return invalid("smallest radius must be positive");
+        }
+        if self.smallest_radius > self.largest_radius {
+            return invalid("smallest radius is larger than largest radius");
+        }
+        for material in [self.material, self.wall_material] {
+            material.validate().map_err(ConfigError::Invalid)?;
+        }
+        self.container
+            .validate(self.largest_radius)
+            .map_err(ConfigError::Invalid)?;
+        let extent = self.container.extent();
+        if extent.x * 2. > self.width || extent.y * 2. > self.height {
+            return invalid("container is larger than the window");
+        }
+        for obstacle in &self.obstacles {
+            obstacle.validate().map_err(ConfigError::Invalid)?;
+        }
+        for emitter in &self.emitters {
+            emitter.validate(self).map_err(ConfigError::Invalid)?;
+        }
+        Ok(())
+    }
+
+    pub fn centre(&self) -> DVec2 {
+        DVec2::new(self.width, self.height) / 2.
+    }
+
+    pub fn tick_duration(&self) -> f64 {
+        1. / self.tps as f64
+    }
+
+    /// The fastest circles may be spawned, in pixels per second: across the
+    /// whole world in one tick.
+    pub fn max_spawn_speed(&self) -> f64 {
+        self.width.max(self.height) * self.tps as f64
+    }
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            width: 800.,
+            height: 800.,
+            tps: 128,
+            max_ticks_per_frame: 16,
+            history: 10.,
+            gravity: 500.,
+            linear_drag: 0.,
+            quadratic_drag: 0.,
+            sleep_speed: 5.,
+            sleep_ticks: 64,
+            repetitions: 4,
+            adaptive: false,
+            max_substeps: 8,
+            parallel: false,
+            substep_travel: 0.5,
+            smallest_radius: 5.,
+            largest_radius: 30.,
+            hose_rate: 30.,
+            hose_velocity: DVec2::ZERO,
+            heavy_density: 20.,
+            material: Material::default(),
+            wall_material: Material::default(),
+            container: ContainerShape::default(),
+            obstacles: Vec::new(),
+            emitters: Vec::new(),
+            background: (0, 0, 0),
+            outer_colour: (30, 30, 30),
+            obstacle_colour: (110, 110, 110),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Parse(toml::de::Error),
+    Override(String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "malformed config: {error}"),
+            Self::Override(entry) => write!(f, "invalid override '{entry}', expected key=value"),
+            Self::Invalid(reason) => write!(f, "invalid config: {reason}"),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        Self::Parse(error)
+    }
+}
@@ -0,0 +1,320 @@
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+
+/// Points per full turn when approximating curves in outlines.
+const OUTLINE_SEGMENTS: usize = 128;
+
+/// The boundary that keeps circles in the world.
+pub trait Container: Send + Sync {
+    /// The distance from `point` to the nearest wall, negative if outside.
+    fn distance(&self, point: DVec2) -> f64;
+
+    /// Where a circle at `position` must move to lie entirely inside.
+    fn constrain(&self, position: DVec2, radius: f64) -> DVec2;
+
+    /// The boundary as a convex polygon, for drawing.
+    fn outline(&self) -> Vec<DVec2>;
+}
+
+pub struct Disc {
+    pub centre: DVec2,
+    pub radius: f64,
+}
+
+impl Container for Disc {
+    fn distance(&self, point: DVec2) -> f64 {
+        self.radius - self.centre.distance(point)
+    }
+
+    fn constrain(&self, position: DVec2, radius: f64) -> DVec2 {
+        let max_dist = self.radius - radius;
+        let offset = position - self.centre;
+        if offset.length_squared() > max_dist * max_dist {
+            offset.normalize() * max_dist + self.centre
+        } else {
+            position
+        }
+    }
+
+    fn outline(&self) -> Vec<DVec2> {
+        let mut points = arc(self.centre, self.radius, 0., TAU, OUTLINE_SEGMENTS);
+        points.pop();
+        points
+    }
+}
+
+/// An axis-aligned rectangle.
+pub struct Rect {
+    pub min: DVec2,
+    pub max: DVec2,
+}
+
+impl Container for Rect {
+    fn distance(&self, point: DVec2) -> f64 {
+        (point - self.min).min(self.max - point).min_element()
+    }
+
+    fn constrain(&self, position: DVec2, radius: f64) -> DVec2 {
+        position.clamp(self.min + radius, self.max - radius)
+    }
+
+    fn outline(&self) -> Vec<DVec2> {
+        vec![
+            self.min,
+            DVec2::new(self.max.x, self.min.y),
+            self.max,
+            DVec2::new(self.min.x, self.max.y),
+        ]
+    }
+}
+
+/// All points within `radius` of the segment from `start` to `end`.
+pub struct Capsule {
+    pub start: DVec2,
+    pub end: DVec2,
+    pub radius: f64,
+}
+
+impl Capsule {
+    fn nearest(&self, point: DVec2) -> DVec2 {
+        nearest_on_segment(point, self.start, self.end)
+    }
+}
+
+impl Container for Capsule {
+    fn distance(&self, point: DVec2) -> f64 {
+        self.radius - self.nearest(point).distance(point)
+    }
+
+    fn constrain(&self, position: DVec2, radius: f64) -> DVec2 {
+        let nearest = self.nearest(position);
+        let max_dist = self.radius - radius;
+        let offset = position - nearest;
+        if offset.length_squared() > max_dist * max_dist {
+            offset.normalize() * max_dist + nearest
+        } else {
+            position
+        }
+    }
+
+    fn outline(&self) -> Vec<DVec2> {
+        let angle = (self.end - self.start).y.atan2((self.end - self.start).x);
+        let segments = OUTLINE_SEGMENTS / 2;
+        let mut points = arc(self.end, self.radius, angle - PI / 2., PI, segments);
+        points.extend(arc(self.start, self.radius, angle + PI / 2., PI, segments));
+        points
+    }
+}
+
+/// A convex polygon, with vertices ordered so that its signed area is positive.
+pub struct Polygon {
+    pub vertices: Vec<DVec2>,
+}
+
+impl Polygon {
+    /// Each edge as a point on it and its inward normal.
+    fn edges(&self) -> impl Iterator<Item = (DVec2, DVec2)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().zip(next).map(|(&a, &b)| {
+            let normal = (b - a).perp().normalize();
+            (a, normal)
+        })
+    }
+}
+
+impl Container for Polygon {
+    fn distance(&self, point: DVec2) -> f64 {
+        self.edges()
+            .map(|(a, normal)| (point - a).dot(normal))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn constrain(&self, position: DVec2, radius: f64) -> DVec2 {
+        let mut position = position;
+        for (a, normal) in self.edges() {
+            let distance = (position - a).dot(normal);
+            if distance < radius {
+                position += normal * (radius - distance);
+            }
+        }
+        position
+    }
+
+    fn outline(&self) -> Vec<DVec2> {
+        self.vertices.clone()
+    }
+}
+
+/// A serialisable description of a container, relative to the centre of the world.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ContainerShape {
+    Disc {
+        radius: f64,
+    },
+    Rect {
+        width: f64,
+        height: f64,
+    },
+    /// A horizontal capsule whose straight section is `length` long.
+    Capsule {
+        length: f64,
+        radius: f64,
+    },
+    /// A convex polygon, in either winding order.
+    Polygon {
+        vertices: Vec<[f64; 2]>,
+    },
+}
+
+impl ContainerShape {
+    /// One of each shape, sized to fit inside a disc of `size`.
+    pub fn presets(size: f64) -> [Self; 4] {
+        [
+            Self::Disc { radius: size },
+            Self::Rect {
+                width: size * 2.,
+                height: size * 1.5,
+            },
+            Self::Capsule {
+                length: size,
+                radius: size / 2.,
+            },
+            Self::Polygon {
+                vertices: (0..6)
+                    .map(|i| {
+                        let vertex = DVec2::from_angle(i as f64 * TAU / 6.) * size;
+                        [vertex.x, vertex.y]
+                    })
+                    .collect(),
+            },
+        ]
+    }
+
+    pub fn build(&self, centre: DVec2) -> Box<dyn Container> {
+        match self {
+            &Self::Disc { radius } => Box::new(Disc { centre, radius }),
+            &Self::Rect { width, height } => {
+                let half = DVec2::new(width, height) / 2.;
+                Box::new(Rect {
+                    min: centre - half,
+                    max: centre + half,
+                })
+            }
+            &Self::Capsule { length, radius } => {
+                let half = DVec2::new(length / 2., 0.);
+                Box::new(Capsule {
+                    start: centre - half,
+                    end: centre + half,
+                    radius,
+                })
+            }
+            Self::Polygon { vertices } => {
+                let mut vertices: Vec<_> = vertices
+                    .iter()
+                    .map(|&vertex| centre + DVec2::from(vertex))
+                    .collect();
+                if signed_area(&vertices) < 0. {
+                    vertices.reverse();
+                }
+                Box::new(Polygon { vertices })
+            }
+        }
+    }
+
+    /// Half the width and height of the bounding box.
+    pub fn extent(&self) -> DVec2 {
+        match self {
+            &Self::Disc { radius } => DVec2::splat(radius),
+            &Self::Rect { width, height } => DVec2::new(width, height) / 2.,
+            &Self::Capsule { length, radius } => DVec2::new(length / 2. + radius, radius),
+            Self::Polygon { vertices } => vertices
+                .iter()
+                .map(|&vertex| DVec2::from(vertex).abs())
+                .fold(DVec2::ZERO, DVec2::max),
+        }
+    }
+
+    /// Checks the shape is well formed and can hold a circle of `radius`.
+    pub fn validate(&self, radius: f64) -> Result<(), String> {
+        let fits = match self {
+            &Self::Disc { radius: outer } => outer >= radius,
+            &Self::Rect { width, height } => width.min(height) >= radius * 2.,
+            &Self::Capsule {
+                length,
+                radius: outer,
+            } => {
+                if length < 0. {
+                    return Err("capsule length must not be negative".to_string());
+                }
+                outer >= radius
+            }
+            Self::Polygon { vertices } => {
+                if vertices.len() < 3 {
+                    return Err("polygon needs at least 3 vertices".to_string());
+                }
+                if vertices.iter().flatten().any(|value| !value.is_finite()) {
+                    return Err("polygon vertices must be finite".to_string());
+                }
+                let polygon = self.build(DVec2::ZERO);
+                let outline = polygon.outline();
+                if !is_convex(&outline) {
+                    return Err("polygon must be convex".to_string());
+                }
+                // the polygon is built around the origin, so this is its inradius
+                // about the centre of the world
+                polygon.distance(DVec2::ZERO) >= radius
+            }
+        };
+        if fits {
+            Ok(())
+        } else {
+            Err("largest radius does not fit in the container".to_string())
+        }
+    }
+}
+
+impl Default for ContainerShape {
+    fn default() -> Self {
+        Self::Disc { radius: 350. }
+    }
+}
+
+fn arc(centre: DVec2, radius: f64, start: f64, sweep: f64, segments: usize) -> Vec<DVec2> {
+    (0..=segments)
+        .map(|i| centre + DVec2::from_angle(start + sweep * i as f64 / segments as f64) * radius)
+        .collect()
+}
+
+pub(crate) fn nearest_on_segment(point: DVec2, start: DVec2, end: DVec2) -> DVec2 {
+    let along = end - start;
+    let t = (point - start).dot(along) / along.length_squared().max(f64::EPSILON);
+    start + along * t.clamp(0., 1.)
+}
+
+/// Twice the signed area, positive when each edge turns towards the perpendicular
+/// of the last, so that `perp` gives inward normals.
+pub(crate) fn signed_area(vertices: &[DVec2]) -> f64 {
+    let next = vertices.iter().cycle().skip(1);
+    vertices.iter().zip(next).map(|(a, b)| a.perp_dot(*b)).sum()
+}
+
+/// Whether a polygon with a positive signed area turns the same way or not at
+/// all at every vertex, and winds around only once, since a star also turns
+/// the same way at every point but winds around more.
+pub(crate) fn is_convex(vertices: &[DVec2]) -> bool {
+    let n = vertices.len();
+    let mut turned = 0.;
+    for i in 0..n {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % n];
+        let c = vertices[(i + 2) % n];
+        let (ab, bc) = (b - a, c - b);
+        if ab.perp_dot(bc) < 0. {
+            return false;
+        }
+        turned += ab.perp_dot(bc).atan2(ab.dot(bc));
+    }
+    (turned - TAU).abs() < 1e-6
+}
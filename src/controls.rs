@@ -0,0 +1,62 @@
+use circles::{Bindings, Button, Inputs, Modifiers, MouseButton};
+use ggez::{
+    input::{
+        keyboard::{self, KeyCode, KeyMods},
+        mouse::{self, MouseButton as M},
+    },
+    Context,
+};
+use glam::IVec2;
+use serde::{de::IntoDeserializer, Deserialize};
+use std::collections::HashMap;
+
+/// Turns the ggez keyboard and mouse state into `Inputs` through a set of
+/// bindings.
+pub struct Controls {
+    bindings: Bindings,
+    /// The key code of each key named in the bindings.
+    keys: HashMap<String, KeyCode>,
+}
+
+impl Controls {
+    /// Fails with a message if a binding names a key that ggez does not have.
+    pub fn new(bindings: Bindings) -> Result<Self, String> {
+        let mut keys = HashMap::new();
+        for (_, chord) in bindings.iter() {
+            if let Button::Key(name) = &chord.button {
+                let code = KeyCode::deserialize(name.as_str().into_deserializer())
+                    .map_err(|_: serde::de::value::Error| format!("unknown key '{name}'"))?;
+                keys.insert(name.clone(), code);
+            }
+        }
+        Ok(Self { bindings, keys })
+    }
+
+    /// Reads the current keyboard and mouse state from ggez into `inputs`.
+    pub fn poll(&self, inputs: &mut Inputs, ctx: &Context) {
+        let pressed_keys = keyboard::pressed_keys(ctx);
+        let down = |button: &Button| match button {
+            Button::Key(name) => pressed_keys.contains(&self.keys[name]),
+            Button::Mouse(button) => {
+                let button = match button {
+                    MouseButton::Left => M::Left,
+                    MouseButton::Right => M::Right,
+                    MouseButton::Middle => M::Middle,
+                };
+                mouse::button_pressed(ctx, button)
+            }
+        };
+        let mods = keyboard::active_mods(ctx);
+        let held = Modifiers {
+            ctrl: mods.contains(KeyMods::CTRL),
+            shift: mods.contains(KeyMods::SHIFT),
+            alt: mods.contains(KeyMods::ALT),
+        };
+
+        let mouse_position = mouse::position(ctx);
+        inputs.update(
+            self.bindings.pressed(down, held),
+            IVec2::new(mouse_position.x as i32, mouse_position.y as i32),
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    config::{SimConfig, MAX_SPAWN_RATE},
+    material::Material,
+    world::{random_colour, Circle, Colour, World},
+};
+use glam::DVec2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A fixed source of circles. The position is in world coordinates, and angles
+/// are in degrees clockwise from the positive x axis.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Emitter {
+    pub position: DVec2,
+    pub direction: f64,
+    /// The width of the cone that circles are fired within, in degrees.
+    pub spread: f64,
+    /// Pixels per second.
+    pub speed: f64,
+    /// Circles per second.
+    pub rate: f64,
+    pub radius: RadiusRule,
+    pub density: f64,
+    pub material: Material,
+    pub colour: ColourRule,
+    /// The emitter pauses while the world holds at least this many circles.
+    pub limit: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case", deny_unknown_fields)]
+pub enum RadiusRule {
+    Fixed { radius: f64 },
+    Uniform { min: f64, max: f64 },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ColourRule {
+    #[default]
+    Random,
+    Fixed {
+        colour: Colour,
+    },
+    /// Cycles through the hues once every `period` seconds.
+    Rainbow {
+        period: f64,
+    },
+}
+
+impl Emitter {
+    /// Checks the emitter against the `config` it belongs to.
+    pub fn validate(&self, config: &SimConfig) -> Result<(), String> {
+        let finite = [
+            self.direction,
+            self.spread,
+            self.speed,
+            self.rate,
+            self.density,
+        ];
+        if !self.position.is_finite() || finite.iter().any(|value| !value.is_finite()) {
+            return Err("emitter values must be finite".to_string());
+        }
+        if !(self.rate > 0. && self.rate <= MAX_SPAWN_RATE) {
+            return Err(format!(
+                "emitter rate must be positive and at most {MAX_SPAWN_RATE}"
+            ));
+        }
+        if self.speed.abs() > config.max_spawn_speed() {
+            return Err("emitter speed must be at most the world size per tick".to_string());
+        }
+        if self.density <= 0. {
+            return Err("emitter density must be positive".to_string());
+        }
+        self.material.validate()?;
+        if self.spread < 0. {
+            return Err("emitter spread must not be negative".to_string());
+        }
+        let (min, max) = self.radius.range();
+        if !(min > 0. && min <= max && max <= config.largest_radius) {
+            return Err("emitter radii must be positive and up to the largest radius".to_string());
+        }
+        if let ColourRule::Rainbow { period } = self.colour {
+            if !(period.is_finite() && period > 0.) {
+                return Err("rainbow period must be positive".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the circles due on tick number `tick` to `world`, skipping any that
+    /// would not fit even at the smallest radius.
+    pub(crate) fn emit(&self, world: &mut World, rng: &mut impl Rng, tick: u64) {
+        let tick_duration = world.config().tick_duration();
+        let emitted = |tick: u64| (tick as f64 * tick_duration * self.rate).floor();
+        let due = emitted(tick + 1) - emitted(tick);
+
+        for _ in 0..due as u64 {
+            if world.circles().len() >= self.limit {
+                return;
+            }
+            let angle = self.direction + self.spread * (rng.gen::<f64>() - 0.5);
+            let velocity = DVec2::from_angle(angle.to_radians()) * self.speed;
+            let (min, max) = self.radius.range();
+            let radius = world.free_radius(self.position, rng.gen_range(min..=max));
+            if radius >= min {
+                let colour = match self.colour {
+                    ColourRule::Random => random_colour(rng),
+                    ColourRule::Fixed { colour } => colour,
+                    ColourRule::Rainbow { period } => {
+                        hue((tick as f64 * tick_duration / period).fract())
+                    }
+                };
+                let circle = Circle::new(self.position, radius, colour)
+                    .with_velocity(velocity * tick_duration)
+                    .with_density(self.density)
+                    .with_material(self.material);
+                world.add(circle);
+            }
+        }
+    }
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self {
+            position: DVec2::ZERO,
+            direction: 90.,
+            spread: 20.,
+            speed: 200.,
+            rate: 10.,
+            radius: RadiusRule::Uniform { min: 5., max: 30. },
+            density: 1.,
+            material: Material::default(),
+            colour: ColourRule::Random,
+            limit: 1000,
+        }
+    }
+}
+
+impl RadiusRule {
+    /// The smallest and largest radius the rule gives.
+    pub fn range(&self) -> (f64, f64) {
+        match *self {
+            Self::Fixed { radius } => (radius, radius),
+            Self::Uniform { min, max } => (min, max),
+        }
+    }
+}
+
+/// A fully saturated colour, `t` of the way around the colour wheel from red.
+fn hue(t: f64) -> Colour {
+    let channel = |offset: f64| {
+        let k = (t * 6. + offset) % 6.;
+        let value = 1. - (k.min(4. - k).clamp(0., 1.));
+        (value * 255.).round() as u8
+    };
+    (channel(5.), channel(3.), channel(1.))
+}
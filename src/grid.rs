@@ -0,0 +1,85 @@
+use glam::{DVec2, IVec2};
+
+/// The furthest cell from the origin in either axis. Circles further out share
+/// the cells at the edge, which only costs extra candidate pairs.
+const MAX_COORD: i32 = 1 << 30;
+
+/// A spatial hash bucketing circles by the cell containing their centre.
+///
+/// As long as the cell size is at least the largest possible sum of two radii,
+/// any two overlapping circles lie in the same or adjacent cells. Cells are
+/// hashed into a table sized from the number of circles rather than the area
+/// they cover, so far flung circles cost nothing extra.
+pub struct Grid {
+    mask: usize,
+    coords: Vec<IVec2>,
+    starts: Vec<usize>,
+    entries: Vec<usize>,
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Self {
+            mask: 0,
+            coords: Vec::new(),
+            starts: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn build(&mut self, positions: impl Iterator<Item = DVec2>, cell_size: f64) {
+        self.coords.clear();
+        self.coords.extend(positions.map(|position| {
+            (position / cell_size)
+                .floor()
+                .as_ivec2()
+                .clamp(IVec2::splat(-MAX_COORD), IVec2::splat(MAX_COORD))
+        }));
+
+        let buckets = (self.coords.len() * 2).next_power_of_two();
+        self.mask = buckets - 1;
+
+        // counting sort of circle indices by bucket, so that each bucket lists
+        // its circles in ascending order
+        self.starts.clear();
+        self.starts.resize(buckets + 1, 0);
+        for i in 0..self.coords.len() {
+            let bucket = self.bucket(self.coords[i]);
+            self.starts[bucket] += 1;
+        }
+        let mut total = 0;
+        for start in self.starts.iter_mut() {
+            total += *start;
+            *start = total;
+        }
+        self.entries.clear();
+        self.entries.resize(self.coords.len(), 0);
+        for i in (0..self.coords.len()).rev() {
+            let bucket = self.bucket(self.coords[i]);
+            self.starts[bucket] -= 1;
+            self.entries[self.starts[bucket]] = i;
+        }
+    }
+
+    /// Circles in the cell of circle `i` and the eight cells around it, as of the
+    /// last call to `build`.
+    pub fn neighbours(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let centre = self.coords[i];
+        (-1..=1)
+            .flat_map(move |y| (-1..=1).map(move |x| centre + IVec2::new(x, y)))
+            .flat_map(move |coord| {
+                let bucket = self.bucket(coord);
+                // other cells can share the bucket
+                self.entries[self.starts[bucket]..self.starts[bucket + 1]]
+                    .iter()
+                    .copied()
+                    .filter(move |&j| self.coords[j] == coord)
+            })
+    }
+
+    fn bucket(&self, coord: IVec2) -> usize {
+        let hash =
+            (coord.x as u32).wrapping_mul(0x9e37_79b1) ^ (coord.y as u32).wrapping_mul(0x85eb_ca77);
+        hash as usize & self.mask
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{config::SimConfig, link::Link, world::Circle};
+use rand_chacha::ChaCha8Rng;
+use std::{collections::VecDeque, rc::Rc};
+
+/// The most circles kept across all the states in a history, about 50 MB.
+/// Big scenes keep fewer states, and so less of the past, to stay within it.
+const MAX_CIRCLES: usize = 1 << 19;
+
+/// A state of the simulation kept in the history: only what changes from tick
+/// to tick, with the config shared between states until it changes.
+#[derive(Clone)]
+pub struct Moment {
+    pub ticks: u64,
+    pub rng: ChaCha8Rng,
+    pub config: Rc<SimConfig>,
+    pub circles: Vec<Circle>,
+    pub links: Vec<Link>,
+}
+
+/// The states a simulation passed through lately, oldest first, so that it can
+/// be run backwards or scrubbed through. Once full, the oldest state is
+/// forgotten for each new one.
+///
+/// While scrubbing, a cursor marks the state being shown, and the states after
+/// it are kept until the simulation resumes from there.
+pub struct History {
+    moments: VecDeque<Moment>,
+    capacity: usize,
+    /// The circles in all the states kept.
+    circles: usize,
+    cursor: Option<usize>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            moments: VecDeque::new(),
+            capacity,
+            circles: 0,
+            cursor: None,
+        }
+    }
+
+    /// `config`, shared with the latest state if it has the same one.
+    pub fn share(&self, config: &SimConfig) -> Rc<SimConfig> {
+        match self.moments.back() {
+            Some(latest) if *latest.config == *config => Rc::clone(&latest.config),
+            _ => Rc::new(config.clone()),
+        }
+    }
+
+    /// Adds the latest state, forgetting any after the cursor.
+    pub fn push(&mut self, moment: Moment) {
+        self.resume();
+        if self.capacity == 0 {
+            return;
+        }
+        while self.moments.len() == self.capacity
+            || !self.moments.is_empty() && self.circles + moment.circles.len() > MAX_CIRCLES
+        {
+            let oldest = self.moments.pop_front().unwrap();
+            self.circles -= oldest.circles.len();
+        }
+        self.circles += moment.circles.len();
+        self.moments.push_back(moment);
+    }
+
+    pub fn scrubbing(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// Starts scrubbing from `present`, the live state, so that scrubbing
+    /// forwards can return to it. The present does not push out the oldest
+    /// state, and is forgotten again on resuming.
+    pub fn start(&mut self, present: Moment) {
+        self.resume();
+        if self.capacity > 0 {
+            self.circles += present.circles.len();
+            self.moments.push_back(present);
+            self.cursor = Some(self.moments.len() - 1);
+        }
+    }
+
+    /// Moves the cursor to the state before it, if there is one.
+    pub fn back(&mut self) -> Option<Moment> {
+        let cursor = self.cursor?.checked_sub(1)?;
+        self.cursor = Some(cursor);
+        Some(self.moments[cursor].clone())
+    }
+
+    /// Moves the cursor to the state after it, if there is one.
+    pub fn forward(&mut self) -> Option<Moment> {
+        let cursor = self.cursor? + 1;
+        let moment = self.moments.get(cursor)?.clone();
+        self.cursor = Some(cursor);
+        Some(moment)
+    }
+
+    /// Stops scrubbing, forgetting the state at the cursor and those after it,
+    /// as the simulation goes on from there.
+    pub fn resume(&mut self) {
+        if let Some(cursor) = self.cursor.take() {
+            for moment in self.moments.drain(cursor..) {
+                self.circles -= moment.circles.len();
+            }
+        }
+    }
+
+    /// How far through the history the cursor is, from 0 at the oldest state to
+    /// 1 at the latest.
+    pub fn position(&self) -> Option<f64> {
+        let cursor = self.cursor?;
+        let last = self.moments.len() - 1;
+        Some(if last == 0 {
+            1.
+        } else {
+            cursor as f64 / last as f64
+        })
+    }
+}
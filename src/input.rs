@@ -1,22 +1,50 @@
 use enum_map::{Enum, EnumMap};
-use ggez::{
-    input::{
-        keyboard::{self, KeyCode as K},
-        mouse::{self, MouseButton as M},
-    },
-    Context,
-};
 use glam::IVec2;
+use serde::{Deserialize, Serialize};
 use std::ops::Index;
 
-#[derive(Clone, Copy, Enum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Enum, Serialize, Deserialize)]
 pub enum Input {
     LeftMouse,
     RightMouse,
+    MiddleMouse,
+    /// Held to make left click link circles.
+    Link,
+    /// Held to spawn circles at the heavy density.
+    Heavy,
+    /// Toggles spawning continuously while left click is held.
+    Hose,
+    NextLinkTool,
+    /// Switches to the next linear drag setting.
+    NextDrag,
+    FewerIterations,
+    MoreIterations,
+    /// Toggles adaptive substepping.
+    Adaptive,
     Clear,
+    NextContainer,
+    /// Places an emitter, or removes the one under the cursor.
+    Emitter,
+    Editor,
+    /// Toggles drawing simulation internals, such as which circles are asleep.
+    Debug,
+    Pause,
+    /// Pauses and advances time by one tick.
+    Step,
+    Slower,
+    Faster,
+    /// Toggles running time backwards through the history.
+    Reverse,
+    /// Held to pause and move back through the history.
+    ScrubBack,
+    /// Held to pause and move forward through the history, up to the present.
+    ScrubForward,
+    Save,
+    Load,
     Quit,
 }
 
+/// The inputs held this frame and last frame.
 pub struct Inputs {
     current: EnumMap<Input, bool>,
     last: EnumMap<Input, bool>,
@@ -32,32 +60,24 @@ impl Inputs {
         }
     }
 
-    pub fn update(&mut self, ctx: &mut Context) {
-        use Input::*;
-
+    /// Starts a new frame in which exactly the `pressed` inputs are held.
+    pub fn update(&mut self, pressed: impl IntoIterator<Item = Input>, mouse_position: IVec2) {
         self.last = self.current;
         self.current.clear();
-        let inputs = &mut self.current;
-
-        for code in keyboard::pressed_keys(ctx) {
-            match code {
-                K::Escape => inputs[Quit] = true,
-                K::Space => inputs[Clear] = true,
-                _ => (),
-            }
-        }
 
-        if mouse::button_pressed(ctx, M::Left) {
-            inputs[LeftMouse] = true;
+        for input in pressed {
+            self.current[input] = true;
         }
 
-        if mouse::button_pressed(ctx, M::Right) {
-            inputs[RightMouse] = true;
-        }
+        self.mouse_position = mouse_position;
+    }
 
-        let mouse_position = mouse::position(ctx);
-        self.mouse_position.x = mouse_position.x as i32;
-        self.mouse_position.y = mouse_position.y as i32;
+    /// The inputs held this frame.
+    pub fn held(&self) -> impl Iterator<Item = Input> + '_ {
+        self.current
+            .iter()
+            .filter(|(_, &held)| held)
+            .map(|(input, _)| input)
     }
 
     pub fn last(&self, input: Input) -> bool {
@@ -69,6 +89,12 @@ impl Inputs {
     }
 }
 
+impl Default for Inputs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Index<Input> for Inputs {
     type Output = bool;
 
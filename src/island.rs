@@ -0,0 +1,41 @@
+/// A union-find over circle indices, grouping circles that touch into islands.
+pub struct Islands {
+    parents: Vec<usize>,
+}
+
+impl Islands {
+    pub fn new() -> Self {
+        Self {
+            parents: Vec::new(),
+        }
+    }
+
+    /// Starts again with `count` circles, each on its own island.
+    pub fn reset(&mut self, count: usize) {
+        self.parents.clear();
+        self.parents.extend(0..count);
+    }
+
+    /// The circle that represents the island containing circle `i`.
+    pub fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // point everything on the way straight at the root
+        let mut i = i;
+        while self.parents[i] != root {
+            let next = self.parents[i];
+            self.parents[i] = root;
+            i = next;
+        }
+        root
+    }
+
+    pub fn join(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        // the lower index wins so that islands do not depend on pair order
+        self.parents[a.max(b)] = a.min(b);
+    }
+}
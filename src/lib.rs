@@ -0,0 +1,35 @@
+//! A circle physics simulation utilising Verlet integration.
+//!
+//! The simulation has no dependency on any windowing or rendering library; the
+//! `circles` binary is a ggez frontend over it.
+
+mod bindings;
+#[cfg(feature = "parallel")]
+mod colouring;
+mod config;
+mod container;
+mod emitter;
+mod grid;
+mod history;
+mod input;
+mod island;
+mod link;
+mod material;
+mod obstacle;
+mod recording;
+mod snapshot;
+mod state;
+mod world;
+
+pub use bindings::{Bindings, BindingsError, Button, Chord, Modifiers, MouseButton};
+pub use config::{ConfigError, SimConfig};
+pub use container::{Capsule, Container, ContainerShape, Disc, Polygon, Rect};
+pub use emitter::{ColourRule, Emitter, RadiusRule};
+pub use input::{Input, Inputs};
+pub use link::{Link, LinkKind};
+pub use material::Material;
+pub use obstacle::Obstacle;
+pub use recording::{Frame, Recorder, Recording};
+pub use snapshot::SnapshotError;
+pub use state::{LinkTool, State};
+pub use world::{BroadPhase, Circle, Colour, World};
@@ -0,0 +1,81 @@
+use crate::world::{self, Circle};
+use serde::{Deserialize, Serialize};
+
+/// A distance constraint between two circles, by index.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    pub a: usize,
+    pub b: usize,
+    pub kind: LinkKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LinkKind {
+    /// Holds the centres exactly `length` apart.
+    Rigid { length: f64 },
+    /// Keeps the distance between the centres between `min` and `max`; a rope has
+    /// a `min` of 0.
+    Range { min: f64, max: f64 },
+    /// Pulls the centres towards `length` apart, correcting `stiffness` (0 to 1)
+    /// of the error per solver iteration.
+    Spring { length: f64, stiffness: f64 },
+}
+
+impl LinkKind {
+    pub fn validate(&self) -> Result<(), String> {
+        let length = |length: f64| length.is_finite() && length >= 0.;
+        match *self {
+            Self::Rigid { length: l } => {
+                if !length(l) {
+                    return Err("link length must be finite and not negative".to_string());
+                }
+            }
+            Self::Range { min, max } => {
+                if !(length(min) && length(max) && min <= max) {
+                    return Err("link range needs 0 <= min <= max".to_string());
+                }
+            }
+            Self::Spring {
+                length: l,
+                stiffness,
+            } => {
+                if !length(l) {
+                    return Err("link length must be finite and not negative".to_string());
+                }
+                if !(stiffness > 0. && stiffness <= 1.) {
+                    return Err("spring stiffness must be above 0 and at most 1".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Link {
+    /// Moves both circles towards satisfying the constraint, sharing the
+    /// correction like collisions do.
+    pub(crate) fn solve(&self, circles: &mut [Circle]) {
+        let a = &circles[self.a];
+        let b = &circles[self.b];
+        let offset = a.position - b.position;
+        let distance = offset.length();
+        let Some(direction) = offset.try_normalize() else {
+            return;
+        };
+
+        let (target, stiffness) = match self.kind {
+            LinkKind::Rigid { length } => (length, 1.),
+            LinkKind::Range { min, max } => (distance.clamp(min, max), 1.),
+            LinkKind::Spring { length, stiffness } => (length, stiffness),
+        };
+        let diff = (target - distance) * stiffness;
+        if diff == 0. {
+            return;
+        }
+
+        let (a, b) = world::shares(a, b);
+        circles[self.a].position += direction * diff * a;
+        circles[self.b].position -= direction * diff * b;
+    }
+}
@@ -1,8 +1,10 @@
 #![windows_subsystem = "windows"]
 
-mod input;
-mod state;
+mod args;
+mod controls;
+mod render;
 
+use circles::{Bindings, Input, Inputs, Recorder, Recording, SimConfig, SnapshotError, State};
 use ggez::{
     conf::{NumSamples, WindowMode, WindowSetup},
     event::{
@@ -10,29 +12,68 @@ use ggez::{
         winit_event::{Event, WindowEvent},
         ControlFlow,
     },
-    timer, ContextBuilder, GameResult,
+    graphics, timer, ContextBuilder, GameError, GameResult,
+};
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::Instant,
 };
-use input::{Input, Inputs};
-use state::State;
 
-const WIDTH: f32 = 800.;
-const HEIGHT: f32 = 800.;
+const DEFAULT_SCENE: &str = "scene.json";
 
 fn main() -> GameResult {
-    let window_mode = WindowMode::default().dimensions(WIDTH, HEIGHT);
+    let args = args::Args::parse().map_err(GameError::CustomError)?;
+    let controls = controls::Controls::new(bindings(&args)?).map_err(GameError::CustomError)?;
+
+    let replay = args
+        .replay
+        .as_deref()
+        .map(load_recording)
+        .transpose()
+        .map_err(file_error)?;
+
+    // the inputs fed to the simulation: the replay's while it lasts, then the live ones
+    let (mut state, mut sim_inputs) = match (&replay, &args.load) {
+        (Some(replay), _) => replay.start(),
+        (None, Some(path)) => (load(path).map_err(file_error)?, Inputs::new()),
+        (None, None) => (
+            State::new(config(&args)?, args.seed.unwrap_or_else(rand::random)),
+            Inputs::new(),
+        ),
+    };
+    let mut replay_frame = 0;
+    let timing = args.timing;
+    let mut draw_times = Vec::new();
+    let scene = args.load.unwrap_or_else(|| PathBuf::from(DEFAULT_SCENE));
+
+    let config = state.world().config();
+    let window_mode = WindowMode::default().dimensions(config.width as f32, config.height as f32);
     let window_setup = WindowSetup::default()
-        .title("circles")
+        .title(&title(&state))
         .samples(NumSamples::Eight)
-        .vsync(true);
+        .vsync(!timing);
 
     let (mut ctx, event_loop) = ContextBuilder::new("circles", "sam")
         .window_mode(window_mode)
         .window_setup(window_setup)
         .build()?;
 
-    let mut state = State::new();
+    let mut renderer = render::Renderer::new(&mut ctx, !args.unbatched)?;
+
     let mut inputs = Inputs::new();
-    inputs.update(&mut ctx);
+    controls.poll(&mut inputs, &ctx);
+    if replay.is_none() {
+        sim_inputs.update(inputs.held(), inputs.mouse_position());
+    }
+
+    let record = args.record;
+    let mut recorder = record
+        .as_deref()
+        .map(|path| start_recording(path, &state, &sim_inputs))
+        .transpose()
+        .map_err(file_error)?;
 
     event_loop.run(move |mut event, _, control_flow| {
         let ctx = &mut ctx;
@@ -48,14 +89,131 @@ fn main() -> GameResult {
         } else if let Event::MainEventsCleared = event {
             ctx.timer_context.tick();
 
-            inputs.update(ctx);
+            controls.poll(&mut inputs, ctx);
 
             if inputs[Input::Quit] {
                 *control_flow = ControlFlow::Exit;
             }
 
-            state.update(timer::delta(ctx).as_secs_f64(), &inputs);
-            state.render(ctx).unwrap();
+            if inputs[Input::Save] && !inputs.last(Input::Save) {
+                if let Err(error) = save(&state, &scene) {
+                    eprintln!("{error}");
+                }
+            }
+
+            if inputs[Input::Load] && !inputs.last(Input::Load) {
+                match load(&scene) {
+                    Ok(loaded) => {
+                        state = loaded;
+                        graphics::set_window_title(ctx, &title(&state));
+                        // frames recorded so far no longer lead to this state
+                        if let Some(path) = &record {
+                            recorder = start_recording(path, &state, &sim_inputs)
+                                .map_err(|error| eprintln!("{error}"))
+                                .ok();
+                        }
+                    }
+                    Err(error) => eprintln!("{error}"),
+                }
+            }
+
+            let frame = replay
+                .as_ref()
+                .and_then(|replay| replay.frames().get(replay_frame));
+            let dt = match frame {
+                Some(frame) => {
+                    replay_frame += 1;
+                    frame.apply(&mut sim_inputs);
+                    frame.dt
+                }
+                None => {
+                    sim_inputs.update(inputs.held(), inputs.mouse_position());
+                    timer::delta(ctx).as_secs_f64()
+                }
+            };
+
+            let recorded = recorder
+                .as_mut()
+                .map(|recorder| recorder.record(dt, &sim_inputs));
+            if let Some(Err(error)) = recorded {
+                eprintln!("{error}");
+                recorder = None;
+            }
+            state.update(dt, &sim_inputs);
+            let start = Instant::now();
+            renderer.render(ctx, &state).unwrap();
+
+            if timing {
+                draw_times.push(start.elapsed().as_secs_f64());
+                if frame.is_none() {
+                    println!("{}", timing_report(&mut draw_times));
+                    *control_flow = ControlFlow::Exit;
+                }
+            }
         }
     });
 }
+
+/// Summarises the time taken to draw each frame of a replay.
+fn timing_report(times: &mut [f64]) -> String {
+    times.sort_by(f64::total_cmp);
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    let percentile = |p: f64| times[((times.len() - 1) as f64 * p).round() as usize];
+    format!(
+        "{} frames drawn: mean {:.3} ms, median {:.3} ms, 95th percentile {:.3} ms",
+        times.len(),
+        mean * 1000.,
+        percentile(0.5) * 1000.,
+        percentile(0.95) * 1000.,
+    )
+}
+
+fn config(args: &args::Args) -> GameResult<SimConfig> {
+    let source = match &args.config {
+        Some(path) => fs::read_to_string(path).map_err(|error| {
+            GameError::CustomError(format!("could not read {}: {error}", path.display()))
+        })?,
+        None => String::new(),
+    };
+    SimConfig::from_toml(&source, &args.overrides)
+        .map_err(|error| GameError::CustomError(error.to_string()))
+}
+
+fn bindings(args: &args::Args) -> GameResult<Bindings> {
+    let path = match &args.bindings {
+        Some(path) => path,
+        None => return Ok(Bindings::default()),
+    };
+    let source = fs::read_to_string(path).map_err(|error| {
+        GameError::CustomError(format!("could not read {}: {error}", path.display()))
+    })?;
+    Bindings::from_toml(&source).map_err(|error| GameError::CustomError(error.to_string()))
+}
+
+fn file_error(error: SnapshotError) -> GameError {
+    GameError::CustomError(error.to_string())
+}
+
+fn title(state: &State) -> String {
+    format!("circles (seed {})", state.seed())
+}
+
+fn save(state: &State, path: &Path) -> Result<(), SnapshotError> {
+    state.save(BufWriter::new(File::create(path)?))
+}
+
+fn load(path: &Path) -> Result<State, SnapshotError> {
+    State::load(BufReader::new(File::open(path)?))
+}
+
+fn start_recording(
+    path: &Path,
+    state: &State,
+    inputs: &Inputs,
+) -> Result<Recorder<BufWriter<File>>, SnapshotError> {
+    Recorder::new(BufWriter::new(File::create(path)?), state, inputs)
+}
+
+fn load_recording(path: &Path) -> Result<Recording, SnapshotError> {
+    Recording::load(BufReader::new(File::open(path)?))
+}
@@ -0,0 +1,79 @@
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+
+/// How a surface responds to contact. The default is perfectly inelastic and
+/// frictionless.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Material {
+    /// The share of the approach speed kept when bouncing apart, from 0 to 1.
+    pub restitution: f64,
+    /// The Coulomb friction coefficient.
+    pub friction: f64,
+}
+
+impl Material {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0. ..=1.).contains(&self.restitution) {
+            return Err("restitution must be between 0 and 1".to_string());
+        }
+        if !(self.friction.is_finite() && self.friction >= 0.) {
+            return Err("friction must not be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// The material of a contact between two surfaces: the bouncier
+    /// restitution, and the geometric mean of the frictions.
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            restitution: self.restitution.max(other.restitution),
+            friction: (self.friction * other.friction).sqrt(),
+        }
+    }
+
+    pub fn is_inert(&self) -> bool {
+        self.restitution == 0. && self.friction == 0.
+    }
+
+    /// The changes to the relative velocity of the surfaces at a contact with
+    /// `normal`, given that velocity after the position solve and `before` it:
+    /// the bounce along the normal, and the friction along the surface.
+    ///
+    /// Approaches slower than `threshold` do not bounce, so that resting contacts
+    /// stay at rest. Friction is limited by how hard the solver pushed the
+    /// surfaces apart.
+    pub(crate) fn response(
+        &self,
+        normal: DVec2,
+        relative: DVec2,
+        before: DVec2,
+        threshold: f64,
+    ) -> (DVec2, DVec2) {
+        let normal_speed = relative.dot(normal);
+        let approach = before.dot(normal);
+        let mut bounce = DVec2::ZERO;
+        let mut push = (normal_speed - approach).max(0.);
+
+        if self.restitution > 0. && approach < -threshold {
+            let target = -approach * self.restitution;
+            if target > normal_speed {
+                bounce = normal * (target - normal_speed);
+                push += target - normal_speed;
+            }
+        }
+
+        let mut slip = DVec2::ZERO;
+        if self.friction > 0. {
+            let tangent = relative - normal * normal_speed;
+            let speed = tangent.length();
+            if speed > 0. {
+                // an impulse along the surface also spins the discs, changing the
+                // velocity there three times as much as the same impulse would
+                // along the normal
+                slip = -tangent / speed * (self.friction * push * 3.).min(speed);
+            }
+        }
+        (bounce, slip)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::container::{is_convex, nearest_on_segment, signed_area};
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+
+/// A fixed shape that circles collide with. Positions are in world coordinates.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Obstacle {
+    Segment {
+        start: DVec2,
+        end: DVec2,
+    },
+    /// Connected segments through each point in turn.
+    Polyline {
+        points: Vec<DVec2>,
+    },
+    /// A solid convex polygon, in either winding order.
+    Polygon {
+        vertices: Vec<DVec2>,
+    },
+    Circle {
+        centre: DVec2,
+        radius: f64,
+    },
+}
+
+impl Obstacle {
+    /// The distance from `point` to the obstacle's surface, negative if inside.
+    pub fn distance(&self, point: DVec2) -> f64 {
+        match self {
+            Self::Circle { centre, radius } => centre.distance(point) - radius,
+            _ => {
+                let distance = self.nearest(point).distance(point);
+                if self.contains(point) {
+                    -distance
+                } else {
+                    distance
+                }
+            }
+        }
+    }
+
+    /// Where a circle at `position` must move to no longer overlap the obstacle.
+    pub fn push_out(&self, position: DVec2, radius: f64) -> DVec2 {
+        if let &Self::Circle { centre, radius: r } = self {
+            let min_dist = r + radius;
+            let offset = position - centre;
+            if offset.length_squared() < min_dist * min_dist {
+                return centre + offset.try_normalize().unwrap_or(DVec2::NEG_Y) * min_dist;
+            }
+            return position;
+        }
+
+        let nearest = self.nearest(position);
+        let offset = position - nearest;
+        if self.contains(position) {
+            // the way out is through the nearest point on the surface
+            let direction = (-offset).try_normalize().unwrap_or(DVec2::NEG_Y);
+            nearest + direction * radius
+        } else if offset.length_squared() < radius * radius {
+            // a circle centred exactly on a segment is pushed up off it
+            let direction = offset.try_normalize().unwrap_or(DVec2::NEG_Y);
+            nearest + direction * radius
+        } else {
+            position
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = |points: &[DVec2]| points.iter().all(|point| point.is_finite());
+        match self {
+            Self::Segment { start, end } => {
+                if !finite(&[*start, *end]) {
+                    return Err("segment ends must be finite".to_string());
+                }
+            }
+            Self::Polyline { points } => {
+                if points.len() < 2 || !finite(points) {
+                    return Err("polyline needs at least 2 finite points".to_string());
+                }
+            }
+            Self::Polygon { vertices } => {
+                if vertices.len() < 3 || !finite(vertices) {
+                    return Err("obstacle polygon needs at least 3 finite vertices".to_string());
+                }
+                let mut vertices = vertices.clone();
+                if signed_area(&vertices) < 0. {
+                    vertices.reverse();
+                }
+                if !is_convex(&vertices) {
+                    return Err("obstacle polygon must be convex".to_string());
+                }
+            }
+            Self::Circle { centre, radius } => {
+                if !centre.is_finite() || !radius.is_finite() || *radius <= 0. {
+                    return Err("obstacle circle needs a finite, positive radius".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with the ends of each edge of a segment, polyline or polygon.
+    fn for_each_edge(&self, mut f: impl FnMut(DVec2, DVec2)) {
+        match self {
+            &Self::Segment { start, end } => f(start, end),
+            Self::Polyline { points } => {
+                for pair in points.windows(2) {
+                    f(pair[0], pair[1]);
+                }
+            }
+            Self::Polygon { vertices } => {
+                for (i, &vertex) in vertices.iter().enumerate() {
+                    f(vertex, vertices[(i + 1) % vertices.len()]);
+                }
+            }
+            Self::Circle { .. } => (),
+        }
+    }
+
+    fn nearest(&self, point: DVec2) -> DVec2 {
+        let mut nearest = point;
+        let mut nearest_dist = f64::INFINITY;
+        self.for_each_edge(|a, b| {
+            let candidate = nearest_on_segment(point, a, b);
+            let dist = candidate.distance_squared(point);
+            if dist < nearest_dist {
+                nearest = candidate;
+                nearest_dist = dist;
+            }
+        });
+        nearest
+    }
+
+    fn contains(&self, point: DVec2) -> bool {
+        let Self::Polygon { vertices } = self else {
+            return false;
+        };
+        let sign = signed_area(vertices).signum();
+        let mut inside = true;
+        self.for_each_edge(|a, b| inside &= (b - a).perp_dot(point - a) * sign >= 0.);
+        inside
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    input::{Input, Inputs},
+    snapshot::{self, Snapshot, SnapshotError},
+    state::State,
+};
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The recording format version written by this build.
+pub const VERSION: u32 = 2;
+
+/// Everything passed to `State::update` in one frame.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Frame {
+    pub dt: f64,
+    pub mouse_position: IVec2,
+    pub held: Vec<Input>,
+}
+
+impl Frame {
+    fn new(dt: f64, inputs: &Inputs) -> Self {
+        Self {
+            dt,
+            mouse_position: inputs.mouse_position(),
+            held: inputs.held().collect(),
+        }
+    }
+
+    /// Advances `inputs` to this frame.
+    pub fn apply(&self, inputs: &mut Inputs) {
+        inputs.update(self.held.iter().copied(), self.mouse_position);
+    }
+}
+
+/// The first line of a recording file, followed by one line per frame.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    start: Snapshot,
+    start_held: Vec<Input>,
+    start_mouse_position: IVec2,
+}
+
+impl Header {
+    fn new(state: &State, inputs: &Inputs) -> Self {
+        Self {
+            version: VERSION,
+            start: state.snapshot(),
+            start_held: inputs.held().collect(),
+            start_mouse_position: inputs.mouse_position(),
+        }
+    }
+}
+
+/// A recorded session: the state it started from and the input of every frame
+/// since. Since `State` is deterministic, replaying the frames reproduces the
+/// session exactly.
+pub struct Recording {
+    header: Header,
+    frames: Vec<Frame>,
+}
+
+impl Recording {
+    /// Starts recording from `state`, with `inputs` as the previous frame's input.
+    pub fn new(state: &State, inputs: &Inputs) -> Self {
+        Self {
+            header: Header::new(state, inputs),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Records one frame. Call this with exactly what is passed to `State::update`.
+    pub fn record(&mut self, dt: f64, inputs: &Inputs) {
+        self.frames.push(Frame::new(dt, inputs));
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// The state and inputs the recording started from.
+    pub fn start(&self) -> (State, Inputs) {
+        let header = &self.header;
+        let mut inputs = Inputs::new();
+        inputs.update(
+            header.start_held.iter().copied(),
+            header.start_mouse_position,
+        );
+        (State::from_snapshot(header.start.clone()), inputs)
+    }
+
+    /// Replays every frame without a window, returning the final state.
+    pub fn replay(&self) -> State {
+        let (mut state, mut inputs) = self.start();
+        for frame in &self.frames {
+            frame.apply(&mut inputs);
+            state.update(frame.dt, &inputs);
+        }
+        state
+    }
+
+    pub fn save(&self, mut writer: impl io::Write) -> Result<(), SnapshotError> {
+        write_line(&mut writer, &self.header)?;
+        for frame in &self.frames {
+            write_line(&mut writer, frame)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a recording, ignoring a final frame cut short by a crash while it
+    /// was being written.
+    pub fn load(mut reader: impl io::BufRead) -> Result<Self, SnapshotError> {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let header: Header = snapshot::read_versioned(line.as_bytes(), VERSION)?;
+        if header.start.version != snapshot::VERSION {
+            return Err(SnapshotError::UnsupportedVersion(
+                header.start.version as u64,
+            ));
+        }
+        header.start.validate()?;
+
+        let mut frames = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            match serde_json::from_str(&line) {
+                Ok(frame) => frames.push(frame),
+                Err(_) if !line.ends_with('\n') => break,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(Self { header, frames })
+    }
+}
+
+/// Writes a recording as the session goes, flushing every frame, so that a
+/// crash loses at most the frame that caused it.
+pub struct Recorder<W: io::Write> {
+    writer: W,
+}
+
+impl<W: io::Write> Recorder<W> {
+    /// Starts recording from `state`, with `inputs` as the previous frame's input.
+    pub fn new(mut writer: W, state: &State, inputs: &Inputs) -> Result<Self, SnapshotError> {
+        write_line(&mut writer, &Header::new(state, inputs))?;
+        writer.flush()?;
+        Ok(Self { writer })
+    }
+
+    /// Records one frame. Call this with exactly what is passed to `State::update`.
+    pub fn record(&mut self, dt: f64, inputs: &Inputs) -> Result<(), SnapshotError> {
+        write_line(&mut self.writer, &Frame::new(dt, inputs))?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+fn write_line(writer: &mut impl io::Write, value: &impl Serialize) -> Result<(), SnapshotError> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
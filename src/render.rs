@@ -0,0 +1,311 @@
+use circles::{Circle, Colour, Obstacle, State};
+use ggez::{
+    graphics::{self, Color, DrawMode, DrawParam, Mesh, MeshBatch, Rect, Text},
+    timer, Context, GameResult,
+};
+use glam::DVec2;
+
+const LINE_WIDTH: f32 = 3.;
+const LINK_WIDTH: f32 = 2.;
+const LINK_COLOUR: (u8, u8, u8) = (220, 220, 220);
+const PREVIEW_COLOUR: (u8, u8, u8) = (200, 200, 80);
+const TEXT_COLOUR: (u8, u8, u8) = (200, 200, 200);
+const EMITTER_COLOUR: (u8, u8, u8) = (80, 200, 220);
+const EMITTER_RADIUS: f64 = 6.;
+/// The length of the line showing an emitter's direction.
+const EMITTER_LENGTH: f64 = 20.;
+/// The outline of circles denser than usual.
+const HEAVY_COLOUR: (u8, u8, u8) = (20, 20, 20);
+/// The colour of sleeping circles in the debug overlay.
+const SLEEP_COLOUR: (u8, u8, u8) = (60, 60, 160);
+const PIN_COLOUR: (u8, u8, u8) = (20, 20, 20);
+/// The line from the centre of each circle that shows how it has turned.
+const MARKER_COLOUR: (u8, u8, u8) = (40, 40, 40);
+/// The radius of the dot drawn on pinned circles, as a share of their own.
+const PIN_SCALE: f64 = 0.3;
+/// The bar along the bottom of the window showing where in the history the
+/// state being shown is.
+const TIMELINE_COLOUR: (u8, u8, u8) = (200, 200, 80);
+const TIMELINE_HEIGHT: f32 = 6.;
+const TIMELINE_MARGIN: f32 = 10.;
+/// The flattening tolerance of the shared unit circle, which is scaled up to
+/// each circle's radius: 0.1 px at a radius of 100.
+const UNIT_TOLERANCE: f32 = 0.001;
+
+/// Draws the simulation, keeping the meshes that circles are drawn with between
+/// frames.
+pub struct Renderer {
+    /// Whether circles are drawn as instances of the cached unit meshes, rather
+    /// than with fresh meshes of their own, which is kept to compare against.
+    batched: bool,
+    discs: MeshBatch,
+    markers: MeshBatch,
+    pins: MeshBatch,
+}
+
+impl Renderer {
+    pub fn new(ctx: &mut Context, batched: bool) -> GameResult<Self> {
+        let white = Color::WHITE;
+        let disc = Mesh::new_circle(ctx, DrawMode::fill(), [0., 0.], 1., UNIT_TOLERANCE, white)?;
+        // from the origin along the x axis, to be stretched out to a radius
+        let bounds = Rect::new(0., -0.5, 1., 1.);
+        let marker = Mesh::new_rectangle(ctx, DrawMode::fill(), bounds, white)?;
+        Ok(Self {
+            batched,
+            discs: MeshBatch::new(disc.clone())?,
+            markers: MeshBatch::new(marker)?,
+            pins: MeshBatch::new(disc)?,
+        })
+    }
+
+    pub fn render(&mut self, ctx: &mut Context, state: &State) -> GameResult {
+        let world = state.world();
+        let config = world.config();
+        let t = state.interpolation();
+
+        graphics::clear(ctx, config.background.into());
+
+        draw_polygon(
+            ctx,
+            &world.container().outline(),
+            config.outer_colour.into(),
+        )?;
+
+        for obstacle in world.obstacles() {
+            let colour = config.obstacle_colour.into();
+            match obstacle {
+                &Obstacle::Segment { start, end } => {
+                    draw_line(ctx, &[start, end], LINE_WIDTH, colour)?
+                }
+                Obstacle::Polyline { points } => draw_line(ctx, points, LINE_WIDTH, colour)?,
+                Obstacle::Polygon { vertices } => draw_polygon(ctx, vertices, colour)?,
+                &Obstacle::Circle { centre, radius } => draw_circle(ctx, centre, radius, colour)?,
+            }
+        }
+
+        for emitter in world.emitters() {
+            let colour = EMITTER_COLOUR.into();
+            let direction = DVec2::from_angle(emitter.direction.to_radians());
+            let end = emitter.position + direction * EMITTER_LENGTH;
+            draw_circle(ctx, emitter.position, EMITTER_RADIUS, colour)?;
+            draw_line(ctx, &[emitter.position, end], LINE_WIDTH, colour)?;
+        }
+
+        if self.batched {
+            self.draw_circles(ctx, state, t)?;
+        } else {
+            for circle in world.circles() {
+                draw_circle_meshes(ctx, state, circle, t)?;
+            }
+        }
+
+        let circles = world.circles();
+        for link in world.links() {
+            let points = [
+                circles[link.a].interpolate(t),
+                circles[link.b].interpolate(t),
+            ];
+            draw_line(ctx, &points, LINK_WIDTH, LINK_COLOUR.into())?;
+        }
+
+        if let Some(i) = state.linking() {
+            let circle = &circles[i];
+            draw_ring(
+                ctx,
+                circle.interpolate(t),
+                circle.radius(),
+                PREVIEW_COLOUR.into(),
+            )?;
+        }
+
+        if let Some((start, end)) = state.drawing() {
+            draw_line(ctx, &[start, end], LINE_WIDTH, PREVIEW_COLOUR.into())?;
+        }
+
+        let mut status = vec![format!("link: {}", state.link_tool().name())];
+        status.push(format!("iterations: {}", config.repetitions));
+        if config.adaptive {
+            status.push("adaptive".to_string());
+        }
+        let drag = config.linear_drag;
+        if drag > 0. {
+            status.push(format!("drag: {drag}"));
+        }
+        if state.debug() {
+            let frame = timer::average_delta(ctx).as_secs_f64() * 1000.;
+            status.push(format!("frame: {frame:.2} ms"));
+            if !self.batched {
+                status.push("unbatched".to_string());
+            }
+            let asleep = circles.iter().filter(|circle| circle.asleep()).count();
+            status.push(format!("{asleep}/{} asleep", circles.len()));
+            status.push(format!("substeps: {}", world.substeps()));
+            status.push(format!("overlap: {:.3} px", world.overlap()));
+        }
+        if state.paused() {
+            status.push("paused".to_string());
+        }
+        if state.reversed() {
+            status.push("reverse".to_string());
+        }
+        if state.time_scale() != 1. {
+            status.push(format!("speed: {}x", state.time_scale()));
+        }
+        if state.slowed_down() {
+            status.push("slowed down".to_string());
+        }
+        if state.hose() {
+            status.push("hose".to_string());
+        }
+        if state.editing() {
+            status.push("editor".to_string());
+        }
+        if let Some(position) = state.scrub_position() {
+            draw_timeline(ctx, config.width, config.height, position)?;
+        }
+
+        let text = Text::new(status.join("  "));
+        graphics::draw(
+            ctx,
+            &text,
+            DrawParam::default()
+                .dest([10., 10.])
+                .color(TEXT_COLOUR.into()),
+        )?;
+
+        graphics::present(ctx)
+    }
+
+    /// Draws every circle in three instanced draw calls: the discs, then the
+    /// markers, then the pins.
+    fn draw_circles(&mut self, ctx: &mut Context, state: &State, t: f64) -> GameResult {
+        self.discs.clear();
+        self.markers.clear();
+        self.pins.clear();
+
+        for circle in state.world().circles() {
+            let centre = to_point(circle.interpolate(t));
+            let radius = circle.radius() as f32;
+            let disc = |radius: f32, colour: Colour| {
+                DrawParam::default()
+                    .dest(centre)
+                    .scale([radius, radius])
+                    .color(colour.into())
+            };
+            let colour = fill_colour(state, circle);
+            if circle.density() > 1. {
+                // the outline is the rim of a dark disc left showing around the fill
+                self.discs.add(disc(radius, HEAVY_COLOUR));
+                self.discs.add(disc(radius - LINK_WIDTH, colour));
+            } else {
+                self.discs.add(disc(radius, colour));
+            }
+            self.markers.add(
+                DrawParam::default()
+                    .dest(centre)
+                    .rotation(circle.interpolate_angle(t) as f32)
+                    .scale([radius, LINK_WIDTH])
+                    .color(MARKER_COLOUR.into()),
+            );
+            if circle.pinned() {
+                self.pins.add(disc(radius * PIN_SCALE as f32, PIN_COLOUR));
+            }
+        }
+
+        self.discs.draw(ctx, DrawParam::default())?;
+        self.markers.draw(ctx, DrawParam::default())?;
+        self.pins.draw(ctx, DrawParam::default())
+    }
+}
+
+/// Draws `circle` with meshes built for it alone.
+fn draw_circle_meshes(ctx: &mut Context, state: &State, circle: &Circle, t: f64) -> GameResult {
+    let centre = circle.interpolate(t);
+    let colour = fill_colour(state, circle);
+    draw_circle(ctx, centre, circle.radius(), colour.into())?;
+    let marker = centre + DVec2::from_angle(circle.interpolate_angle(t)) * circle.radius();
+    draw_line(ctx, &[centre, marker], LINK_WIDTH, MARKER_COLOUR.into())?;
+    if circle.density() > 1. {
+        draw_ring(ctx, centre, circle.radius(), HEAVY_COLOUR.into())?;
+    }
+    if circle.pinned() {
+        draw_circle(ctx, centre, circle.radius() * PIN_SCALE, PIN_COLOUR.into())?;
+    }
+    Ok(())
+}
+
+fn fill_colour(state: &State, circle: &Circle) -> Colour {
+    if state.debug() && circle.asleep() {
+        SLEEP_COLOUR
+    } else {
+        circle.colour()
+    }
+}
+
+/// An outlined bar across the bottom of a window of `width` by `height`,
+/// filled `position` of the way along.
+fn draw_timeline(ctx: &mut Context, width: f64, height: f64, position: f64) -> GameResult {
+    let bounds = Rect::new(
+        TIMELINE_MARGIN,
+        height as f32 - TIMELINE_MARGIN - TIMELINE_HEIGHT,
+        width as f32 - TIMELINE_MARGIN * 2.,
+        TIMELINE_HEIGHT,
+    );
+    let colour = TIMELINE_COLOUR.into();
+    let outline = Mesh::new_rectangle(ctx, DrawMode::stroke(1.), bounds, colour)?;
+    graphics::draw(ctx, &outline, DrawParam::default())?;
+    let filled = Rect {
+        w: bounds.w * position as f32,
+        ..bounds
+    };
+    if filled.w > 0. {
+        let fill = Mesh::new_rectangle(ctx, DrawMode::fill(), filled, colour)?;
+        graphics::draw(ctx, &fill, DrawParam::default())?;
+    }
+    Ok(())
+}
+
+fn draw_polygon(ctx: &mut Context, points: &[DVec2], colour: Color) -> GameResult {
+    let points: Vec<_> = points.iter().copied().map(to_point).collect();
+    let mesh = graphics::Mesh::new_polygon(ctx, DrawMode::fill(), &points, colour)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())
+}
+
+fn draw_line(ctx: &mut Context, points: &[DVec2], width: f32, colour: Color) -> GameResult {
+    // ggez rejects lines without two distinct points
+    if points.windows(2).all(|pair| pair[0] == pair[1]) {
+        return Ok(());
+    }
+    let points: Vec<_> = points.iter().copied().map(to_point).collect();
+    let mesh = graphics::Mesh::new_line(ctx, &points, width, colour)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())
+}
+
+fn draw_circle(ctx: &mut Context, centre: DVec2, radius: f64, colour: Color) -> GameResult {
+    let mesh = graphics::Mesh::new_circle(
+        ctx,
+        DrawMode::fill(),
+        to_point(centre),
+        radius as f32,
+        0.1,
+        colour,
+    )?;
+    graphics::draw(ctx, &mesh, DrawParam::default())
+}
+
+/// A circle outline drawn just inside `radius`.
+fn draw_ring(ctx: &mut Context, centre: DVec2, radius: f64, colour: Color) -> GameResult {
+    let mesh = graphics::Mesh::new_circle(
+        ctx,
+        DrawMode::stroke(LINK_WIDTH),
+        to_point(centre),
+        radius as f32 - LINK_WIDTH / 2.,
+        0.1,
+        colour,
+    )?;
+    graphics::draw(ctx, &mesh, DrawParam::default())
+}
+
+fn to_point(point: DVec2) -> [f32; 2] {
+    [point.x as f32, point.y as f32]
+}
@@ -0,0 +1,144 @@
+use crate::{
+    config::{ConfigError, SimConfig},
+    link::Link,
+    world::Circle,
+};
+use glam::DVec2;
+use rand_chacha::ChaCha8Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{error::Error, fmt, io};
+
+/// The snapshot format version written by this build. Bump it whenever the
+/// layout of `Snapshot` or anything in it changes, including `SimConfig`, so
+/// that files from other builds are rejected as such rather than loaded with
+/// defaults or reported as malformed.
+pub const VERSION: u32 = 11;
+
+/// The complete serialisable state of a `State`.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    pub version: u32,
+    pub seed: u64,
+    pub rng: ChaCha8Rng,
+    pub accumulator: f64,
+    /// Ticks run since the simulation was created.
+    pub ticks: u64,
+    pub config: SimConfig,
+    pub circles: Vec<Circle>,
+    pub links: Vec<Link>,
+}
+
+impl Snapshot {
+    /// Checks the parts of the snapshot that serde cannot.
+    pub fn validate(&self) -> Result<(), SnapshotError> {
+        self.config.validate()?;
+        let count = self.circles.len();
+        let size = DVec2::new(self.config.width, self.config.height);
+        let within = |point: DVec2| point.cmpge(DVec2::ZERO).all() && point.cmple(size).all();
+        for (i, circle) in self.circles.iter().enumerate() {
+            let position = within(circle.position) && within(circle.last_position);
+            let radius = circle.radius.is_finite() && circle.radius > 0.;
+            let density = circle.density.is_finite() && circle.density > 0.;
+            let spin = circle.angle.is_finite() && circle.angular_velocity.is_finite();
+            if !position || !radius || !density || !spin || circle.material.validate().is_err() {
+                return Err(SnapshotError::InvalidCircle(i));
+            }
+        }
+        for &link in &self.links {
+            let ends = link.a < count && link.b < count && link.a != link.b;
+            if !ends || link.kind.validate().is_err() {
+                return Err(SnapshotError::InvalidLink(link));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write(&self, writer: impl io::Write) -> Result<(), SnapshotError> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn read(reader: impl io::Read) -> Result<Self, SnapshotError> {
+        read_versioned(reader, VERSION)
+    }
+}
+
+/// Reads a JSON object with a top level `version` field, checking the version
+/// before the layout so that files from other versions give a useful error.
+pub(crate) fn read_versioned<T: DeserializeOwned>(
+    reader: impl io::Read,
+    expected: u32,
+) -> Result<T, SnapshotError> {
+    let value: serde_json::Value = serde_json::from_reader(reader)?;
+    let version = value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or(SnapshotError::MissingVersion)?;
+    if version != expected as u64 {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// An error reading or writing a snapshot or recording file.
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    MissingVersion,
+    UnsupportedVersion(u64),
+    Config(ConfigError),
+    InvalidCircle(usize),
+    InvalidLink(Link),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not access file: {error}"),
+            Self::Format(error) => write!(f, "malformed file: {error}"),
+            Self::MissingVersion => write!(f, "file has no version"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "file version {version} is not supported")
+            }
+            Self::Config(error) => write!(f, "{error}"),
+            Self::InvalidCircle(i) => write!(f, "circle {i} is invalid"),
+            Self::InvalidLink(link) => {
+                write!(f, "link between {} and {} is invalid", link.a, link.b)
+            }
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Format(error) => Some(error),
+            Self::Config(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ConfigError> for SnapshotError {
+    fn from(error: ConfigError) -> Self {
+        Self::Config(error)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(error: serde_json::Error) -> Self {
+        if error.is_io() {
+            Self::Io(error.into())
+        } else {
+            Self::Format(error)
+        }
+    }
+}
@@ -1,190 +1,682 @@
-use crate::input::{self, Inputs};
-use ggez::{
-    graphics::{self, Color, DrawMode, DrawParam},
-    Context, GameResult,
+use crate::{
+    config::SimConfig,
+    container::ContainerShape,
+    emitter::{Emitter, RadiusRule},
+    history::{History, Moment},
+    input::{self, Inputs},
+    link::{Link, LinkKind},
+    obstacle::Obstacle,
+    snapshot::{self, Snapshot, SnapshotError},
+    world::{random_colour, Circle, World},
 };
 use glam::DVec2;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::{io, mem, rc::Rc};
 
-const TPS: u64 = 128;
-const GRAVITY: f64 = 500.;
-const REPETIIONS: u8 = 4;
-const SMALLEST_RADIUS: f64 = 5.;
-const LARGEST_RADIUS: f64 = 30.;
-const OUTER_RADIUS: f64 = 350.;
+/// How far the mouse must move for a right-drag in the editor to count as one.
+const DRAG_THRESHOLD: f64 = 3.;
 
-const BACKGROUND: (u8, u8, u8) = (0, 0, 0);
-const OUTER_COLOUR: (u8, u8, u8) = (30, 30, 30);
+/// How close the cursor must be to an emitter to remove it.
+const EMITTER_TOLERANCE: f64 = 10.;
 
-const TICK_DURATION: f64 = 1. / TPS as f64;
-const TICK_GRAVITY: f64 = GRAVITY * TICK_DURATION * TICK_DURATION;
+/// The linear drag settings switched between at runtime, from none to
+/// viscous.
+const DRAG_PRESETS: [f64; 4] = [0., 0.5, 2., 8.];
 
-use super::{HEIGHT, WIDTH};
-const CENTRE: DVec2 = DVec2::new(WIDTH as f64 / 2., HEIGHT as f64 / 2.);
+/// The most solver iterations that can be chosen at runtime.
+const MAX_REPETITIONS: u32 = 64;
 
+/// How long, in seconds, the slowed down notice stays up after time is dropped.
+const SLOWED_DOWN_DISPLAY: f64 = 1.;
+
+/// The speeds that time can run at, as multiples of real time.
+const TIME_SCALES: [f64; 6] = [0.1, 0.25, 0.5, 1., 2., 4.];
+
+/// How many times faster than real time holding a scrub key moves through the
+/// history.
+const SCRUB_SPEED: f64 = 4.;
+
+/// Ticks between the states kept in the history.
+const HISTORY_INTERVAL: u64 = 4;
+
+/// The share of a spring link's error corrected per solver iteration.
+const SPRING_STIFFNESS: f64 = 0.05;
+
+/// The kind of link made by shift-clicking two circles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkTool {
+    Rigid,
+    Rope,
+    Spring,
+}
+
+impl LinkTool {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rigid => "rigid",
+            Self::Rope => "rope",
+            Self::Spring => "spring",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Rigid => Self::Rope,
+            Self::Rope => Self::Spring,
+            Self::Spring => Self::Rigid,
+        }
+    }
+
+    fn kind(self, length: f64) -> LinkKind {
+        match self {
+            Self::Rigid => LinkKind::Rigid { length },
+            Self::Rope => LinkKind::Range {
+                min: 0.,
+                max: length,
+            },
+            Self::Spring => LinkKind::Spring {
+                length,
+                stiffness: SPRING_STIFFNESS,
+            },
+        }
+    }
+}
+
+/// A circle held by the mouse.
+#[derive(Clone, Copy)]
+struct Grab {
+    index: usize,
+    /// From the cursor to the circle's centre.
+    offset: DVec2,
+    /// Whether the circle was pinned before it was grabbed, and so stays put
+    /// when released.
+    pinned: bool,
+}
+
+/// Drives a `World` from frame inputs, stepping it at a fixed tick rate.
+///
+/// All randomness comes from an RNG seeded at construction, so the same seed and
+/// the same sequence of `update` calls always produce the same simulation.
 pub struct State {
     accumulator: f64,
-    circles: Vec<Circle>,
+    ticks: u64,
+    world: World,
+    seed: u64,
+    rng: ChaCha8Rng,
+    editing: bool,
+    debug: bool,
+    drawing: Option<(DVec2, DVec2)>,
+    link_tool: LinkTool,
+    linking: Option<usize>,
+    grab: Option<Grab>,
+    hose: bool,
+    /// Time since the hose last spawned a circle.
+    hose_timer: f64,
+    /// Time left showing that the last frames could not keep up.
+    slowed_down: f64,
+    paused: bool,
+    time_scale: f64,
+    reversed: bool,
+    /// Time run backwards since the last state was taken from the history.
+    rewind_timer: f64,
+    /// Time a scrub key has been held since the cursor last moved.
+    scrub_timer: f64,
+    history: History,
 }
 
 impl State {
-    pub fn new() -> Self {
+    /// Creates an empty simulation. `config` should already be validated.
+    pub fn new(config: SimConfig, seed: u64) -> Self {
         Self {
             accumulator: 0.,
-            circles: Vec::new(),
+            ticks: 0,
+            history: History::new(history_capacity(&config)),
+            world: World::new(config),
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            editing: false,
+            debug: false,
+            drawing: None,
+            link_tool: LinkTool::Rigid,
+            linking: None,
+            grab: None,
+            hose: false,
+            hose_timer: 0.,
+            slowed_down: 0.,
+            paused: false,
+            time_scale: 1.,
+            reversed: false,
+            rewind_timer: 0.,
+            scrub_timer: 0.,
+        }
+    }
+
+    /// Writes the full simulation state, including the RNG, as a versioned JSON
+    /// snapshot.
+    pub fn save(&self, writer: impl io::Write) -> Result<(), SnapshotError> {
+        self.snapshot().write(writer)
+    }
+
+    /// Restores a state written by `save`. The restored state continues exactly as
+    /// the saved one would have.
+    pub fn load(reader: impl io::Read) -> Result<Self, SnapshotError> {
+        let snapshot = Snapshot::read(reader)?;
+        snapshot.validate()?;
+        Ok(Self::from_snapshot(snapshot))
+    }
+
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: snapshot::VERSION,
+            seed: self.seed,
+            rng: self.rng.clone(),
+            accumulator: self.accumulator,
+            ticks: self.ticks,
+            config: self.world.config().clone(),
+            circles: self.circles(),
+            links: self.world.links().to_vec(),
+        }
+    }
+
+    /// The parts of the state kept in the history.
+    fn moment(&self) -> Moment {
+        Moment {
+            ticks: self.ticks,
+            rng: self.rng.clone(),
+            config: self.history.share(self.world.config()),
+            circles: self.circles(),
+            links: self.world.links().to_vec(),
+        }
+    }
+
+    /// The circles as they would be if let go.
+    fn circles(&self) -> Vec<Circle> {
+        let mut circles = self.world.circles().to_vec();
+        // a grabbed circle is only pinned while it is held
+        if let Some(grab) = self.grab {
+            circles[grab.index].pinned = grab.pinned;
+        }
+        circles
+    }
+
+    pub(crate) fn from_snapshot(snapshot: Snapshot) -> Self {
+        Self {
+            accumulator: snapshot.accumulator,
+            ticks: snapshot.ticks,
+            history: History::new(history_capacity(&snapshot.config)),
+            world: World::restore(snapshot.config, snapshot.circles, snapshot.links),
+            seed: snapshot.seed,
+            rng: snapshot.rng,
+            editing: false,
+            debug: false,
+            drawing: None,
+            link_tool: LinkTool::Rigid,
+            linking: None,
+            grab: None,
+            hose: false,
+            hose_timer: 0.,
+            slowed_down: 0.,
+            paused: false,
+            time_scale: 1.,
+            reversed: false,
+            rewind_timer: 0.,
+            scrub_timer: 0.,
         }
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Whether right click draws obstacles rather than deleting circles.
+    pub fn editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Whether to draw simulation internals.
+    pub fn debug(&self) -> bool {
+        self.debug
+    }
+
+    /// The start and current end of the segment being drawn in the editor.
+    pub fn drawing(&self) -> Option<(DVec2, DVec2)> {
+        self.drawing
+    }
+
+    /// Whether holding left click spawns circles continuously.
+    pub fn hose(&self) -> bool {
+        self.hose
+    }
+
+    /// Whether frames recently took too long to simulate in full, so some time
+    /// was dropped.
+    pub fn slowed_down(&self) -> bool {
+        self.slowed_down > 0.
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// How fast time runs, as a multiple of real time.
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Whether time is running backwards through the history.
+    pub fn reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// How far through the history the state being shown is, from 0 at the
+    /// oldest to 1 at the present, while scrubbing or running backwards.
+    pub fn scrub_position(&self) -> Option<f64> {
+        self.history.position()
+    }
+
+    /// How many ticks have been simulated.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn link_tool(&self) -> LinkTool {
+        self.link_tool
+    }
+
+    /// The circle chosen as the first end of a new link.
+    pub fn linking(&self) -> Option<usize> {
+        self.linking
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// How far the simulation is between the last tick and the next, from 0 to 1.
+    pub fn interpolation(&self) -> f64 {
+        self.accumulator / self.world.config().tick_duration()
+    }
+
     pub fn update(&mut self, dt: f64, inputs: &Inputs) {
         use input::Input::*;
 
         let mouse = inputs.mouse_position().as_dvec2();
 
         if inputs[Clear] && !inputs.last(Clear) {
-            self.circles.clear();
+            self.release();
+            self.world.clear();
+            self.linking = None;
+        }
+
+        if inputs[NextContainer] && !inputs.last(NextContainer) {
+            self.next_container();
+        }
+
+        if inputs[Emitter] && !inputs.last(Emitter) {
+            self.toggle_emitter(mouse);
+        }
+
+        if inputs[NextLinkTool] && !inputs.last(NextLinkTool) {
+            self.link_tool = self.link_tool.next();
         }
 
+        if inputs[NextDrag] && !inputs.last(NextDrag) {
+            let drag = self.world.config().linear_drag;
+            let next = DRAG_PRESETS.into_iter().find(|&preset| preset > drag);
+            self.world.set_linear_drag(next.unwrap_or(0.));
+        }
+
+        let repetitions = self.world.config().repetitions;
+        if inputs[FewerIterations] && !inputs.last(FewerIterations) && repetitions > 1 {
+            self.world.set_repetitions(repetitions - 1);
+        }
+        if inputs[MoreIterations] && !inputs.last(MoreIterations) && repetitions < MAX_REPETITIONS {
+            self.world.set_repetitions(repetitions + 1);
+        }
+
+        if inputs[Adaptive] && !inputs.last(Adaptive) {
+            let adaptive = self.world.config().adaptive;
+            self.world.set_adaptive(!adaptive);
+        }
+
+        if inputs[Hose] && !inputs.last(Hose) {
+            self.hose = !self.hose;
+        }
+
+        let density = if inputs[Heavy] {
+            self.world.config().heavy_density
+        } else {
+            1.
+        };
         if inputs[LeftMouse] && !inputs.last(LeftMouse) {
-            let mut upper = LARGEST_RADIUS;
-            let lower = SMALLEST_RADIUS;
-            let radius = lower + random().max(random()) * (upper - lower);
-            for circle in &self.circles {
-                let distance = circle.position.distance(mouse) - circle.radius;
-                if distance < upper {
-                    upper = distance;
+            if inputs[Link] {
+                self.link_at(mouse);
+            } else if let Some(index) = self.world.circle_at(mouse) {
+                self.grab(index, mouse);
+            } else if self.hose {
+                self.spawn(mouse, self.world.config().hose_velocity, density);
+                self.hose_timer = 0.;
+            } else {
+                self.spawn(mouse, DVec2::ZERO, density);
+            }
+        } else if !inputs[LeftMouse] {
+            self.release();
+        } else if self.hose && self.grab.is_none() && !inputs[Link] {
+            let config = self.world.config();
+            let interval = 1. / config.hose_rate;
+            let velocity = config.hose_velocity;
+            self.hose_timer += dt;
+            while self.hose_timer >= interval {
+                self.spawn(mouse, velocity, density);
+                self.hose_timer -= interval;
+            }
+        }
+
+        if inputs[MiddleMouse] && !inputs.last(MiddleMouse) {
+            match (self.world.circle_at(mouse), &mut self.grab) {
+                // a held circle is pinned or freed where it is dropped
+                (Some(i), Some(grab)) if i == grab.index => grab.pinned = !grab.pinned,
+                (Some(i), _) => {
+                    let pinned = self.world.circles()[i].pinned();
+                    self.world.set_pinned(i, !pinned);
+                }
+                (None, _) => (),
+            }
+        }
+
+        if inputs[Debug] && !inputs.last(Debug) {
+            self.debug = !self.debug;
+        }
+
+        if inputs[Editor] && !inputs.last(Editor) {
+            self.editing = !self.editing;
+            self.drawing = None;
+        }
+
+        if self.editing {
+            self.edit(inputs);
+        } else if inputs[RightMouse] && !inputs.last(RightMouse) {
+            self.release();
+            self.world.remove_at(mouse);
+            self.linking = None;
+        }
+
+        if inputs[Pause] && !inputs.last(Pause) {
+            self.paused = !self.paused;
+        }
+
+        if inputs[Slower] && !inputs.last(Slower) {
+            let slower = TIME_SCALES
+                .into_iter()
+                .rev()
+                .find(|&scale| scale < self.time_scale);
+            self.time_scale = slower.unwrap_or(self.time_scale);
+        }
+        if inputs[Faster] && !inputs.last(Faster) {
+            let faster = TIME_SCALES
+                .into_iter()
+                .find(|&scale| scale > self.time_scale);
+            self.time_scale = faster.unwrap_or(self.time_scale);
+        }
+
+        if inputs[Reverse] && !inputs.last(Reverse) {
+            self.release();
+            self.reversed = !self.reversed;
+            self.rewind_timer = 0.;
+        }
+
+        let tick_duration = self.world.config().tick_duration();
+        let interval = tick_duration * HISTORY_INTERVAL as f64;
+        let scrub_back = inputs[ScrubBack] && !inputs[ScrubForward];
+        let scrub_forward = inputs[ScrubForward] && !inputs[ScrubBack];
+        if scrub_back || scrub_forward {
+            self.paused = true;
+            self.reversed = false;
+            // a tap moves one state, and holding on keeps going
+            let mut steps = 0;
+            if !inputs.last(ScrubBack) && !inputs.last(ScrubForward) {
+                self.scrub_timer = 0.;
+                steps = 1;
+            } else {
+                self.scrub_timer += dt * SCRUB_SPEED;
+                while self.scrub_timer >= interval {
+                    self.scrub_timer -= interval;
+                    steps += 1;
                 }
             }
-            let distance = OUTER_RADIUS - CENTRE.distance(mouse);
-            if distance < upper {
-                upper = distance;
+            for _ in 0..steps {
+                if scrub_back {
+                    self.rewind();
+                } else if let Some(moment) = self.history.forward() {
+                    self.restore(moment);
+                }
             }
-            if upper >= lower {
-                self.circles
-                    .push(Circle::new(mouse, radius.min(upper), random_colour()));
+        }
+
+        self.slowed_down -= dt;
+        if inputs[Step] && !inputs.last(Step) {
+            self.paused = true;
+            if self.reversed {
+                self.rewind();
+            } else {
+                self.tick();
             }
         }
+        if self.paused {
+            return;
+        }
 
-        if inputs[RightMouse] && !inputs.last(RightMouse) {
-            let mut i = 0;
-            while i < self.circles.len() {
-                if self.circles[i].point_within(mouse) {
-                    self.circles.swap_remove(i);
-                } else {
-                    i += 1;
+        let dt = dt * self.time_scale;
+        if self.reversed {
+            self.rewind_timer += dt;
+            while self.rewind_timer >= interval {
+                self.rewind_timer -= interval;
+                self.rewind();
+                if !self.reversed {
+                    break;
                 }
             }
+            return;
         }
 
+        let max_ticks = self.world.config().max_ticks_per_frame;
         self.accumulator += dt;
-        while self.accumulator >= TICK_DURATION {
+        // a grabbed circle moves evenly towards the cursor over this frame's ticks
+        let ticks = (self.accumulator / tick_duration)
+            .floor()
+            .min(max_ticks as f64);
+        let drag = self.grab.map(|grab| {
+            let circle = &self.world.circles()[grab.index];
+            let target = self
+                .world
+                .container()
+                .constrain(mouse + grab.offset, circle.radius());
+            (grab.index, circle.position(), target)
+        });
+        let mut tick = 0.;
+        while self.accumulator >= tick_duration && tick < max_ticks as f64 {
+            tick += 1.;
+            if let Some((index, start, target)) = drag {
+                let t = (tick / ticks).min(1.);
+                self.world.drag(index, start.lerp(target, t));
+            }
             self.tick();
-            self.accumulator -= TICK_DURATION;
+            self.accumulator -= tick_duration;
+        }
+        // ticks that would only make the next frame longer still are dropped
+        if self.accumulator >= tick_duration {
+            self.accumulator %= tick_duration;
+            self.slowed_down = SLOWED_DOWN_DISPLAY;
         }
     }
+}
 
+impl State {
     fn tick(&mut self) {
-        for circle in self.circles.iter_mut() {
-            let last = circle.position;
-            circle.position += circle.position - circle.last_position;
-            circle.last_position = last;
-            circle.position.y += TICK_GRAVITY;
-        }
-
-        for _ in 0..REPETIIONS {
-            for i in 0..self.circles.len() {
-                for j in i + 1..self.circles.len() {
-                    let a = &self.circles[i];
-                    let b = &self.circles[j];
-                    let dist_sq = a.position.distance_squared(b.position);
-                    let sum_radii = a.radius + b.radius;
-                    if dist_sq < sum_radii * sum_radii {
-                        let offset = (a.position - b.position).normalize();
-                        let diff = sum_radii - dist_sq.sqrt();
-                        let a = a.radius * a.radius;
-                        let b = b.radius * b.radius;
-                        let total = a + b;
-                        self.circles[i].position += offset * diff * b / total;
-                        self.circles[j].position -= offset * diff * a / total;
-                    }
-                }
-            }
-            for circle in self.circles.iter_mut() {
-                let max_dist = OUTER_RADIUS - circle.radius;
-                let offset = circle.position - CENTRE;
-                if offset.length_squared() > max_dist * max_dist {
-                    circle.position = offset.normalize() * max_dist + CENTRE;
-                }
+        // time going on from a scrubbed state forgets the old future
+        self.history.resume();
+        if self.ticks.is_multiple_of(HISTORY_INTERVAL) {
+            self.history.push(self.moment());
+        }
+        for i in 0..self.world.emitters().len() {
+            let emitter = self.world.emitters()[i];
+            emitter.emit(&mut self.world, &mut self.rng, self.ticks);
+        }
+        self.world.step();
+        self.ticks += 1;
+    }
+
+    /// Goes back to the previous state in the history, or pauses at the oldest
+    /// once the history runs out.
+    fn rewind(&mut self) {
+        if !self.history.scrubbing() {
+            self.history.start(self.moment());
+        }
+        match self.history.back() {
+            Some(moment) => self.restore(moment),
+            None => {
+                self.reversed = false;
+                self.paused = true;
             }
         }
     }
 
-    pub fn render(&self, ctx: &mut Context) -> GameResult {
-        let t = self.accumulator / TICK_DURATION;
+    /// Shows a state from the history.
+    fn restore(&mut self, moment: Moment) {
+        self.release();
+        self.linking = None;
+        // the states were taken at the start of a tick
+        self.accumulator = 0.;
+        self.ticks = moment.ticks;
+        self.rng = moment.rng;
+        let config = Rc::unwrap_or_clone(moment.config);
+        self.world = World::restore(config, moment.circles, moment.links);
+    }
 
-        graphics::clear(ctx, BACKGROUND.into());
+    /// Adds a circle at `point` moving at `velocity`, shrunk to fit the free space
+    /// there, or nothing if even the smallest circle would not fit.
+    fn spawn(&mut self, point: DVec2, velocity: DVec2, density: f64) {
+        let config = self.world.config();
+        let lower = config.smallest_radius;
+        let largest = config.largest_radius;
+        let t = self.rng.gen::<f64>().max(self.rng.gen());
+        let radius = lower + t * (largest - lower);
+        let upper = self.world.free_radius(point, largest);
+        if upper >= lower {
+            let circle = Circle::new(point, radius.min(upper), random_colour(&mut self.rng))
+                .with_velocity(velocity * config.tick_duration())
+                .with_density(density)
+                .with_material(config.material);
+            self.world.add(circle);
+        }
+    }
 
-        draw_circle(ctx, CENTRE, OUTER_RADIUS, OUTER_COLOUR.into())?;
+    /// Holds circle `index` under the cursor until the mouse is released.
+    fn grab(&mut self, index: usize, point: DVec2) {
+        let circle = &self.world.circles()[index];
+        self.grab = Some(Grab {
+            index,
+            offset: circle.position() - point,
+            pinned: circle.pinned(),
+        });
+        self.world.set_pinned(index, true);
+    }
 
-        for circle in &self.circles {
-            circle.render(ctx, t)?;
+    /// Lets go of the grabbed circle, which keeps the cursor's last motion
+    /// unless it is pinned.
+    fn release(&mut self) {
+        if let Some(grab) = self.grab.take() {
+            self.world.set_pinned(grab.index, grab.pinned);
         }
+    }
 
-        graphics::present(ctx)
+    /// The first click picks a circle, the second links it to another at their
+    /// current distance. Clicking empty space or the same circle cancels.
+    fn link_at(&mut self, point: DVec2) {
+        let Some(b) = self.world.circle_at(point) else {
+            self.linking = None;
+            return;
+        };
+        match self.linking.take() {
+            Some(a) if a != b => {
+                let circles = self.world.circles();
+                let length = circles[a].position().distance(circles[b].position());
+                self.world.add_link(Link {
+                    a,
+                    b,
+                    kind: self.link_tool.kind(length),
+                });
+            }
+            Some(_) => (),
+            None => self.linking = Some(b),
+        }
     }
-}
 
-struct Circle {
-    position: DVec2,
-    last_position: DVec2,
-    radius: f64,
-    colour: Color,
-}
+    /// Right-drag draws a segment; right click without dragging removes the
+    /// obstacles under the cursor.
+    fn edit(&mut self, inputs: &Inputs) {
+        use input::Input::*;
 
-impl Circle {
-    fn new(position: DVec2, radius: f64, colour: Color) -> Self {
-        Self {
-            position,
-            last_position: position,
-            radius,
-            colour,
+        let mouse = inputs.mouse_position().as_dvec2();
+
+        if inputs[RightMouse] && !inputs.last(RightMouse) {
+            self.drawing = Some((mouse, mouse));
         }
-    }
 
-    fn render(&self, ctx: &mut Context, t: f64) -> GameResult {
-        draw_circle(
-            ctx,
-            self.last_position.lerp(self.position, t),
-            self.radius,
-            self.colour,
-        )
+        if let Some((start, _)) = self.drawing {
+            if inputs[RightMouse] {
+                self.drawing = Some((start, mouse));
+            } else {
+                self.drawing = None;
+                if start.distance(mouse) < DRAG_THRESHOLD {
+                    self.world.remove_obstacles_at(mouse, DRAG_THRESHOLD);
+                } else {
+                    self.world
+                        .add_obstacle(Obstacle::Segment { start, end: mouse });
+                }
+            }
+        }
     }
 
-    fn point_within(&self, pos: DVec2) -> bool {
-        self.position.distance_squared(pos) < self.radius * self.radius
+    /// Removes the emitters under the cursor, or places one there if there are
+    /// none.
+    fn toggle_emitter(&mut self, point: DVec2) {
+        if !self.world.remove_emitters_at(point, EMITTER_TOLERANCE) {
+            let config = self.world.config();
+            let emitter = Emitter {
+                position: point,
+                radius: RadiusRule::Uniform {
+                    min: config.smallest_radius,
+                    max: config.largest_radius,
+                },
+                ..Emitter::default()
+            };
+            self.world.add_emitter(emitter);
+        }
     }
-}
 
-fn draw_circle(ctx: &mut Context, centre: DVec2, radius: f64, colour: Color) -> GameResult {
-    let mesh = graphics::Mesh::new_circle(
-        ctx,
-        DrawMode::fill(),
-        [centre.x as f32, centre.y as f32],
-        radius as f32,
-        0.1,
-        colour,
-    )?;
-    graphics::draw(ctx, &mesh, DrawParam::default())
-}
+    /// Switches to the next kind of container, sized to fit the world.
+    fn next_container(&mut self) {
+        let config = self.world.config();
+        let size = config.width.min(config.height) * 0.4375;
+        let presets = ContainerShape::presets(size);
+        let current = presets
+            .iter()
+            .position(|preset| mem::discriminant(preset) == mem::discriminant(&config.container))
+            .unwrap_or(0);
 
-fn random_colour() -> Color {
-    (
-        55 + (random() * 200.) as u8,
-        55 + (random() * 200.) as u8,
-        55 + (random() * 200.) as u8,
-    )
-        .into()
+        let next = (1..presets.len())
+            .map(|offset| &presets[(current + offset) % presets.len()])
+            .find(|preset| preset.validate(config.largest_radius).is_ok());
+        if let Some(next) = next {
+            self.world.set_container(next.clone());
+        }
+    }
 }
 
-fn random() -> f64 {
-    rand::thread_rng().gen()
+/// How many states the history holds to cover the configured time.
+fn history_capacity(config: &SimConfig) -> usize {
+    let ticks = config.history * config.tps as f64;
+    (ticks / HISTORY_INTERVAL as f64).ceil() as usize
 }
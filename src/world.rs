@@ -0,0 +1,803 @@
+#[cfg(feature = "parallel")]
+use crate::colouring::Colouring;
+use crate::{
+    config::SimConfig,
+    container::{Container, ContainerShape},
+    emitter::Emitter,
+    grid::Grid,
+    island::Islands,
+    link::Link,
+    material::Material,
+    obstacle::Obstacle,
+};
+use glam::DVec2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::mem;
+
+/// How close two surfaces must be to count as touching when applying materials.
+const CONTACT_SLOP: f64 = 0.1;
+
+pub type Colour = (u8, u8, u8);
+
+pub(crate) fn random_colour(rng: &mut impl Rng) -> Colour {
+    (
+        55 + (rng.gen::<f64>() * 200.) as u8,
+        55 + (rng.gen::<f64>() * 200.) as u8,
+        55 + (rng.gen::<f64>() * 200.) as u8,
+    )
+}
+
+/// How the solver finds the pairs of circles that might be touching.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BroadPhase {
+    /// Checks every pair of circles.
+    BruteForce,
+    /// Checks neighbouring cells of a spatial hash, built once per solver
+    /// iteration. Circles moved during an iteration stay in their old cells
+    /// until the next one, so a pair pushed into contact from further apart
+    /// than a cell is only found then; this only happens when circles start
+    /// deeply overlapped, and otherwise the results match `BruteForce` exactly.
+    Grid,
+}
+
+/// The physics simulation: a set of circles inside a container.
+pub struct World {
+    config: SimConfig,
+    container: Box<dyn Container>,
+    circles: Vec<Circle>,
+    links: Vec<Link>,
+    broad_phase: BroadPhase,
+    grid: Grid,
+    candidates: Vec<usize>,
+    #[cfg(feature = "parallel")]
+    colouring: Colouring,
+    /// Each circle's velocity before the position solve, for applying materials.
+    velocities: Vec<DVec2>,
+    islands: Islands,
+    /// The substeps taken by the last tick.
+    substeps: u32,
+    /// The deepest overlap between two circles found by the last solver
+    /// iteration of the last tick.
+    overlap: f64,
+    /// Whether each island, by its representative circle, has moved lately.
+    restless: Vec<bool>,
+}
+
+impl World {
+    pub fn new(config: SimConfig) -> Self {
+        Self {
+            container: config.container.build(config.centre()),
+            config,
+            circles: Vec::new(),
+            links: Vec::new(),
+            broad_phase: BroadPhase::Grid,
+            grid: Grid::new(),
+            candidates: Vec::new(),
+            #[cfg(feature = "parallel")]
+            colouring: Colouring::new(),
+            velocities: Vec::new(),
+            islands: Islands::new(),
+            substeps: 1,
+            overlap: 0.,
+            restless: Vec::new(),
+        }
+    }
+
+    /// Rebuilds a world from its parts exactly as they were, without waking
+    /// anything as `add_link` would. The links should already be valid.
+    pub(crate) fn restore(config: SimConfig, circles: Vec<Circle>, links: Vec<Link>) -> Self {
+        let mut world = Self::new(config);
+        world.circles = circles;
+        world.links = links;
+        world
+    }
+
+    pub fn config(&self) -> &SimConfig {
+        &self.config
+    }
+
+    pub fn centre(&self) -> DVec2 {
+        self.config.centre()
+    }
+
+    pub fn container(&self) -> &dyn Container {
+        self.container.as_ref()
+    }
+
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.config.obstacles
+    }
+
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) {
+        self.config.obstacles.push(obstacle);
+        self.wake_all();
+    }
+
+    /// Removes every obstacle within `tolerance` of `point`.
+    pub fn remove_obstacles_at(&mut self, point: DVec2, tolerance: f64) {
+        self.config
+            .obstacles
+            .retain(|obstacle| obstacle.distance(point) > tolerance);
+        self.wake_all();
+    }
+
+    pub fn emitters(&self) -> &[Emitter] {
+        &self.config.emitters
+    }
+
+    pub fn add_emitter(&mut self, emitter: Emitter) {
+        self.config.emitters.push(emitter);
+    }
+
+    /// Removes every emitter within `tolerance` of `point`, returning whether
+    /// there were any.
+    pub fn remove_emitters_at(&mut self, point: DVec2, tolerance: f64) -> bool {
+        let count = self.config.emitters.len();
+        self.config
+            .emitters
+            .retain(|emitter| emitter.position.distance(point) > tolerance);
+        self.config.emitters.len() < count
+    }
+
+    /// Replaces the container. Circles left outside are pushed in on the next step.
+    pub fn set_container(&mut self, shape: ContainerShape) {
+        self.container = shape.build(self.centre());
+        self.config.container = shape;
+        self.wake_all();
+    }
+
+    pub fn set_linear_drag(&mut self, drag: f64) {
+        self.config.linear_drag = drag;
+    }
+
+    /// Sets the solver iterations per step, which must be at least 1.
+    pub fn set_repetitions(&mut self, repetitions: u32) {
+        assert!(repetitions >= 1);
+        self.config.repetitions = repetitions;
+    }
+
+    pub fn set_adaptive(&mut self, adaptive: bool) {
+        self.config.adaptive = adaptive;
+    }
+
+    pub fn substeps(&self) -> u32 {
+        self.substeps
+    }
+
+    /// The deepest overlap between two circles, in pixels, left for the last
+    /// solver iteration of the last tick to correct. Fewer iterations or larger
+    /// steps leave more.
+    pub fn overlap(&self) -> f64 {
+        self.overlap
+    }
+
+    pub fn circles(&self) -> &[Circle] {
+        &self.circles
+    }
+
+    pub fn set_broad_phase(&mut self, broad_phase: BroadPhase) {
+        self.broad_phase = broad_phase;
+    }
+
+    pub fn add(&mut self, circle: Circle) {
+        self.circles.push(circle);
+    }
+
+    /// Removes circle `i`, and its links. The last circle takes its index.
+    pub fn remove(&mut self, i: usize) {
+        // whatever the circle was holding up must fall
+        let removed = &self.circles[i];
+        let (position, reach) = (removed.position, removed.radius + CONTACT_SLOP);
+        for circle in self.circles.iter_mut() {
+            if circle.position.distance(position) < circle.radius + reach {
+                circle.wake();
+            }
+        }
+        for link in &self.links {
+            if link.a == i || link.b == i {
+                self.circles[link.a].wake();
+                self.circles[link.b].wake();
+            }
+        }
+
+        let last = self.circles.len() - 1;
+        self.circles.swap_remove(i);
+        self.links.retain(|link| link.a != i && link.b != i);
+        for link in self.links.iter_mut() {
+            if link.a == last {
+                link.a = i;
+            }
+            if link.b == last {
+                link.b = i;
+            }
+        }
+    }
+
+    /// Removes every circle containing `point`.
+    pub fn remove_at(&mut self, point: DVec2) {
+        let mut i = 0;
+        while i < self.circles.len() {
+            if self.circles[i].point_within(point) {
+                self.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.circles.clear();
+        self.links.clear();
+    }
+
+    /// Fixes circle `i` in place, stopping it, or frees it with whatever motion
+    /// it was last given by `drag`.
+    pub fn set_pinned(&mut self, i: usize, pinned: bool) {
+        let circle = &mut self.circles[i];
+        circle.pinned = pinned;
+        circle.wake();
+        if pinned {
+            circle.last_position = circle.position;
+            circle.angular_velocity = 0.;
+        }
+    }
+
+    /// Moves circle `i` to `position` as though it travelled there over the last
+    /// tick, stopping at the container wall. Only pinned circles keep this
+    /// motion, until they are unpinned.
+    pub fn drag(&mut self, i: usize, position: DVec2) {
+        let circle = &mut self.circles[i];
+        circle.last_position = circle.position;
+        circle.position = self.container.constrain(position, circle.radius);
+    }
+
+    /// The index of a circle containing `point`, if any.
+    pub fn circle_at(&self, point: DVec2) -> Option<usize> {
+        self.circles
+            .iter()
+            .position(|circle| circle.point_within(point))
+    }
+
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// Adds a constraint between two circles.
+    ///
+    /// # Panics
+    ///
+    /// If either index is out of bounds, both are the same circle, or the kind
+    /// of link is invalid.
+    pub fn add_link(&mut self, link: Link) {
+        assert!(link.a < self.circles.len() && link.b < self.circles.len());
+        assert_ne!(link.a, link.b);
+        if let Err(error) = link.kind.validate() {
+            panic!("{error}");
+        }
+        self.circles[link.a].wake();
+        self.circles[link.b].wake();
+        self.links.push(link);
+    }
+
+    /// The largest radius, up to `upper`, that a circle at `point` could have
+    /// without overlapping another circle, an obstacle or the container wall.
+    pub fn free_radius(&self, point: DVec2, upper: f64) -> f64 {
+        let mut upper = upper;
+        for circle in &self.circles {
+            let distance = circle.position.distance(point) - circle.radius;
+            if distance < upper {
+                upper = distance;
+            }
+        }
+        for obstacle in &self.config.obstacles {
+            let distance = obstacle.distance(point);
+            if distance < upper {
+                upper = distance;
+            }
+        }
+        let distance = self.container.distance(point);
+        if distance < upper {
+            upper = distance;
+        }
+        upper
+    }
+
+    /// Advances the simulation by one tick.
+    pub fn step(&mut self) {
+        let substeps = self.substeps_needed();
+        self.substeps = substeps;
+        self.overlap = 0.;
+        if substeps == 1 {
+            self.substep(self.config.tick_duration());
+        } else {
+            // velocities are distances per step, so they shrink for substeps
+            let scale = substeps as f64;
+            self.scale_velocities(1. / scale);
+            for _ in 0..substeps {
+                self.substep(self.config.tick_duration() / scale);
+            }
+            self.scale_velocities(scale);
+        }
+
+        if self.config.sleep_speed > 0. {
+            self.update_sleep();
+        }
+    }
+
+    /// How many substeps the next tick needs so that no circle moves further
+    /// than `substep_travel` of the smallest radius in one.
+    fn substeps_needed(&self) -> u32 {
+        if !self.config.adaptive {
+            return 1;
+        }
+        let fastest = self
+            .circles
+            .iter()
+            .filter(|circle| circle.moves())
+            .map(|circle| circle.velocity().length())
+            .fold(0., f64::max);
+        let travel = self.config.substep_travel * self.config.smallest_radius;
+        ((fastest / travel).ceil() as u32).clamp(1, self.config.max_substeps)
+    }
+
+    fn scale_velocities(&mut self, scale: f64) {
+        for circle in self.circles.iter_mut().filter(|circle| circle.moves()) {
+            circle.last_position = circle.position - circle.velocity() * scale;
+            circle.angular_velocity *= scale;
+        }
+    }
+
+    fn substep(&mut self, duration: f64) {
+        let tick_gravity = self.config.gravity * duration * duration;
+        // the exact solutions for each kind of drag over a step
+        let linear_drag = (-self.config.linear_drag * duration).exp();
+        let quadratic_drag = self.config.quadratic_drag;
+
+        for circle in self.circles.iter_mut().filter(|circle| circle.moves()) {
+            let velocity = circle.velocity();
+            let drag = linear_drag / (1. + quadratic_drag * velocity.length());
+            let last = circle.position;
+            circle.position += velocity * drag;
+            circle.last_position = last;
+            circle.position.y += tick_gravity;
+            circle.angular_velocity *= linear_drag;
+            circle.angle += circle.angular_velocity;
+        }
+
+        let materials = !self.config.wall_material.is_inert()
+            || self
+                .circles
+                .iter()
+                .any(|circle| !circle.material.is_inert());
+        if materials {
+            self.velocities.clear();
+            self.velocities
+                .extend(self.circles.iter().map(Circle::velocity));
+        }
+
+        #[cfg(feature = "parallel")]
+        if self.config.parallel {
+            self.colour_pairs();
+        }
+        for repetition in 0..self.config.repetitions {
+            let overlap = self.solve_contacts();
+            if repetition + 1 == self.config.repetitions {
+                self.overlap = self.overlap.max(overlap);
+            }
+            for link in &self.links {
+                if !(self.circles[link.a].asleep && self.circles[link.b].asleep) {
+                    link.solve(&mut self.circles);
+                }
+            }
+            self.constrain_to_walls();
+        }
+
+        if materials {
+            // bounces only for approaches faster than a couple of steps of gravity
+            self.apply_materials(tick_gravity.abs() * 2.);
+        }
+    }
+
+    /// Moves circles out of obstacles and into the container.
+    fn constrain_to_walls(&mut self) {
+        let obstacles = &self.config.obstacles;
+        let container = &self.container;
+        let constrain = |circle: &mut Circle| {
+            if circle.moves() {
+                for obstacle in obstacles {
+                    circle.position = obstacle.push_out(circle.position, circle.radius);
+                }
+                circle.position = container.constrain(circle.position, circle.radius);
+            }
+        };
+        #[cfg(feature = "parallel")]
+        if self.config.parallel {
+            use rayon::prelude::*;
+            self.circles.par_iter_mut().for_each(constrain);
+            return;
+        }
+        self.circles.iter_mut().for_each(constrain);
+    }
+
+    /// Pushes apart every pair of overlapping circles once, returning the
+    /// deepest overlap found.
+    fn solve_contacts(&mut self) -> f64 {
+        #[cfg(feature = "parallel")]
+        if self.config.parallel {
+            return self.colouring.solve(&mut self.circles, separate);
+        }
+        let mut overlap = 0_f64;
+        self.for_each_pair(|circles, i, j| overlap = overlap.max(collide(circles, i, j)));
+        overlap
+    }
+
+    /// Sorts the pairs that might touch into colours for `solve_contacts` to
+    /// solve on all cores, once for every iteration of a step. Pairs that only
+    /// come within reach during the step are left for the next one.
+    ///
+    /// Every candidate pair is kept, touching or not, so that the colours of a
+    /// resting pile do not change from step to step and it can settle.
+    #[cfg(feature = "parallel")]
+    fn colour_pairs(&mut self) {
+        let mut colouring = mem::replace(&mut self.colouring, Colouring::new());
+        colouring.reset(self.circles.len());
+        self.for_each_pair(|_, i, j| colouring.add(i, j));
+        self.colouring = colouring;
+    }
+
+    /// Puts islands of touching circles that have all been still for long enough
+    /// to sleep, and wakes sleeping circles on islands that have started moving.
+    fn update_sleep(&mut self) {
+        let threshold = self.config.sleep_speed * self.config.tick_duration();
+        let ticks = self.config.sleep_ticks;
+        for circle in self.circles.iter_mut().filter(|circle| !circle.pinned) {
+            let spin = circle.angular_velocity.abs() * circle.radius;
+            if circle.velocity().length() < threshold && spin < threshold {
+                circle.still = circle.still.saturating_add(1);
+            } else {
+                circle.still = 0;
+            }
+        }
+
+        // pinned circles are like walls, and do not join islands together
+        let mut islands = mem::replace(&mut self.islands, Islands::new());
+        islands.reset(self.circles.len());
+        self.for_each_pair(|circles, i, j| {
+            let (a, b) = (&circles[i], &circles[j]);
+            let reach = a.radius + b.radius + CONTACT_SLOP;
+            let touching = a.position.distance_squared(b.position) < reach * reach;
+            if touching && !a.pinned && !b.pinned {
+                islands.join(i, j);
+            }
+        });
+        for link in &self.links {
+            if !self.circles[link.a].pinned && !self.circles[link.b].pinned {
+                islands.join(link.a, link.b);
+            }
+        }
+
+        self.restless.clear();
+        self.restless.resize(self.circles.len(), false);
+        for (i, circle) in self.circles.iter().enumerate() {
+            if !circle.pinned && circle.still < ticks {
+                self.restless[islands.find(i)] = true;
+            }
+        }
+        for (i, circle) in self.circles.iter_mut().enumerate() {
+            if circle.pinned {
+                continue;
+            }
+            if self.restless[islands.find(i)] {
+                circle.wake();
+            } else if !circle.asleep {
+                circle.asleep = true;
+                circle.last_position = circle.position;
+                circle.angular_velocity = 0.;
+            }
+        }
+        self.islands = islands;
+    }
+
+    fn wake_all(&mut self) {
+        for circle in self.circles.iter_mut() {
+            circle.wake();
+        }
+    }
+
+    /// Calls `f` with each pair of circles that might be touching, in the same
+    /// order whatever the broad phase. Pairs of sleeping circles are skipped, and
+    /// in the rest `i` is either before `j` or awake while `j` sleeps.
+    fn for_each_pair(&mut self, mut f: impl FnMut(&mut [Circle], usize, usize)) {
+        if self.circles.iter().all(|circle| circle.asleep) {
+            return;
+        }
+        let wanted = |circles: &[Circle], i: usize, j: usize| j > i || circles[j].asleep;
+        match self.broad_phase {
+            BroadPhase::BruteForce => {
+                for i in 0..self.circles.len() {
+                    if self.circles[i].asleep {
+                        continue;
+                    }
+                    for j in 0..self.circles.len() {
+                        if j != i && wanted(&self.circles, i, j) {
+                            f(&mut self.circles, i, j);
+                        }
+                    }
+                }
+            }
+            BroadPhase::Grid => {
+                let largest = self
+                    .circles
+                    .iter()
+                    .map(|circle| circle.radius)
+                    .fold(0., f64::max);
+                // the slop keeps circles that are only just touching neighbours
+                self.grid.build(
+                    self.circles.iter().map(|circle| circle.position),
+                    largest * 2. + CONTACT_SLOP,
+                );
+                for i in 0..self.circles.len() {
+                    if self.circles[i].asleep {
+                        continue;
+                    }
+                    // same pair order as the brute force loop
+                    let circles = &self.circles;
+                    self.candidates.clear();
+                    self.candidates.extend(
+                        self.grid
+                            .neighbours(i)
+                            .filter(|&j| j != i && wanted(circles, i, j)),
+                    );
+                    self.candidates.sort_unstable();
+                    for &j in &self.candidates {
+                        f(&mut self.circles, i, j);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adjusts the velocity at each contact for restitution and friction, by
+    /// comparing it with the velocity before the position solve.
+    fn apply_materials(&mut self, threshold: f64) {
+        let velocities = mem::take(&mut self.velocities);
+
+        self.for_each_pair(|circles, i, j| {
+            let (a, b) = (&circles[i], &circles[j]);
+            let offset = a.position - b.position;
+            let reach = a.radius + b.radius + CONTACT_SLOP;
+            let material = a.material.combine(b.material);
+            if offset.length_squared() >= reach * reach || material.is_inert() {
+                return;
+            }
+            let Some(normal) = offset.try_normalize() else {
+                return;
+            };
+            let relative = a.surface_velocity(-normal) - b.surface_velocity(normal);
+            let before = velocities[i] - velocities[j];
+            let (bounce, slip) = material.response(normal, relative, before, threshold);
+            let (radius_a, radius_b) = (a.radius, b.radius);
+            let (a, b) = shares(a, b);
+            // a third of the friction slows the circles and the rest spins them
+            let linear = bounce + slip / 3.;
+            let twist = normal.perp_dot(slip) * 2. / 3.;
+            circles[i].last_position -= linear * a;
+            circles[j].last_position += linear * b;
+            circles[i].angular_velocity -= twist * a / radius_a;
+            circles[j].angular_velocity -= twist * b / radius_b;
+        });
+
+        let wall = self.config.wall_material;
+        for (circle, &before) in self.circles.iter_mut().zip(&velocities) {
+            let material = circle.material.combine(wall);
+            if !circle.moves() || material.is_inert() {
+                continue;
+            }
+            // a wall is touching if it would push a slightly larger circle
+            let position = circle.position;
+            let reach = circle.radius + CONTACT_SLOP;
+            let walls = self
+                .config
+                .obstacles
+                .iter()
+                .map(|obstacle| obstacle.push_out(position, reach))
+                .chain([self.container.constrain(position, reach)]);
+            for pushed in walls {
+                if let Some(normal) = (pushed - position).try_normalize() {
+                    let relative = circle.surface_velocity(-normal);
+                    let (bounce, slip) = material.response(normal, relative, before, threshold);
+                    circle.last_position -= bounce + slip / 3.;
+                    circle.angular_velocity -= normal.perp_dot(slip) * 2. / 3. / circle.radius;
+                }
+            }
+        }
+
+        self.velocities = velocities;
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Circle {
+    pub(crate) position: DVec2,
+    pub(crate) last_position: DVec2,
+    pub(crate) radius: f64,
+    /// Mass per unit area.
+    pub(crate) density: f64,
+    pub(crate) material: Material,
+    /// Radians clockwise from the positive x axis.
+    pub(crate) angle: f64,
+    /// Radians per tick.
+    pub(crate) angular_velocity: f64,
+    colour: Colour,
+    /// A pinned circle never moves, and others resolve contacts with it alone.
+    pub(crate) pinned: bool,
+    /// Ticks in a row that the circle has been slower than the sleep speed.
+    still: u32,
+    /// A sleeping circle is not moved until something disturbs it.
+    asleep: bool,
+}
+
+impl Circle {
+    pub fn new(position: DVec2, radius: f64, colour: Colour) -> Self {
+        Self {
+            position,
+            last_position: position,
+            radius,
+            density: 1.,
+            material: Material::default(),
+            angle: 0.,
+            angular_velocity: 0.,
+            colour,
+            pinned: false,
+            still: 0,
+            asleep: false,
+        }
+    }
+
+    pub fn with_density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Sets the distance the circle moves each tick.
+    pub fn with_velocity(mut self, velocity: DVec2) -> Self {
+        self.last_position = self.position - velocity;
+        self
+    }
+
+    pub fn position(&self) -> DVec2 {
+        self.position
+    }
+
+    pub fn last_position(&self) -> DVec2 {
+        self.last_position
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+
+    pub fn material(&self) -> Material {
+        self.material
+    }
+
+    /// The distance the circle moved over the last tick.
+    pub fn velocity(&self) -> DVec2 {
+        self.position - self.last_position
+    }
+
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    pub fn angular_velocity(&self) -> f64 {
+        self.angular_velocity
+    }
+
+    /// The velocity of the point on the edge in `direction`, a unit vector,
+    /// including the spin.
+    pub fn surface_velocity(&self, direction: DVec2) -> DVec2 {
+        self.velocity() + direction.perp() * self.angular_velocity * self.radius
+    }
+
+    pub fn mass(&self) -> f64 {
+        self.density * self.radius * self.radius
+    }
+
+    pub fn colour(&self) -> Colour {
+        self.colour
+    }
+
+    pub fn pinned(&self) -> bool {
+        self.pinned
+    }
+
+    pub fn asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// Whether the circle moves by itself, being neither pinned nor asleep.
+    fn moves(&self) -> bool {
+        !self.pinned && !self.asleep
+    }
+
+    fn wake(&mut self) {
+        if self.asleep {
+            self.asleep = false;
+            self.still = 0;
+        }
+    }
+
+    /// The position `t` of the way from the previous tick to the current one.
+    pub fn interpolate(&self, t: f64) -> DVec2 {
+        self.last_position.lerp(self.position, t)
+    }
+
+    /// The angle `t` of the way from the previous tick to the current one.
+    pub fn interpolate_angle(&self, t: f64) -> f64 {
+        self.angle - self.angular_velocity * (1. - t)
+    }
+
+    pub fn point_within(&self, pos: DVec2) -> bool {
+        self.position.distance_squared(pos) < self.radius * self.radius
+    }
+}
+
+/// Pushes circles `i` and `j` apart, returning how far they overlapped.
+fn collide(circles: &mut [Circle], i: usize, j: usize) -> f64 {
+    let (a, b) = pair_mut(circles, i, j);
+    separate(a, b)
+}
+
+/// Borrows two different circles at once.
+pub(crate) fn pair_mut(circles: &mut [Circle], i: usize, j: usize) -> (&mut Circle, &mut Circle) {
+    if i < j {
+        let (start, end) = circles.split_at_mut(j);
+        (&mut start[i], &mut end[0])
+    } else {
+        let (start, end) = circles.split_at_mut(i);
+        (&mut end[0], &mut start[j])
+    }
+}
+
+/// Pushes two circles apart, returning how far they overlapped.
+pub(crate) fn separate(a: &mut Circle, b: &mut Circle) -> f64 {
+    let dist_sq = a.position.distance_squared(b.position);
+    let sum_radii = a.radius + b.radius;
+    if dist_sq < sum_radii * sum_radii {
+        let offset = (a.position - b.position).normalize();
+        let diff = sum_radii - dist_sq.sqrt();
+        let (share_a, share_b) = shares(a, b);
+        a.position += offset * diff * share_a;
+        b.position -= offset * diff * share_b;
+        diff
+    } else {
+        0.
+    }
+}
+
+/// How much of a correction between two circles each one takes. The heavier
+/// circle moves less, and a pinned circle not at all.
+pub(crate) fn shares(a: &Circle, b: &Circle) -> (f64, f64) {
+    match (a.pinned, b.pinned) {
+        (true, true) => (0., 0.),
+        (true, false) => (0., 1.),
+        (false, true) => (1., 0.),
+        (false, false) => {
+            let a = a.mass();
+            let b = b.mass();
+            let total = a + b;
+            (b / total, a / total)
+        }
+    }
+}
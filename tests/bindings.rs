@@ -0,0 +1,110 @@
+use circles::{Bindings, BindingsError, Button, Chord, Input, Modifiers, MouseButton};
+
+fn chord(source: &str) -> Chord {
+    source.parse().unwrap()
+}
+
+fn key(name: &str) -> Button {
+    Button::Key(name.to_string())
+}
+
+#[test]
+fn defaults_match_the_usual_controls() {
+    let bindings = Bindings::default();
+    assert_eq!(bindings.chords(Input::Save), [chord("S")]);
+    assert_eq!(
+        bindings.chords(Input::Link),
+        [chord("LShift"), chord("RShift")]
+    );
+    assert_eq!(bindings.chords(Input::LeftMouse), [chord("MouseLeft")]);
+    assert!(Bindings::from_toml("").is_ok());
+}
+
+#[test]
+fn chords_parse_and_print() {
+    let parsed = chord("Ctrl+Shift+Z");
+    assert_eq!(
+        parsed.modifiers,
+        Modifiers {
+            ctrl: true,
+            shift: true,
+            alt: false,
+        }
+    );
+    assert_eq!(parsed.button, key("Z"));
+    assert_eq!(parsed.to_string(), "Ctrl+Shift+Z");
+    assert_eq!(
+        chord("Alt+MouseMiddle").button,
+        Button::Mouse(MouseButton::Middle)
+    );
+
+    for invalid in ["", "Ctrl+", "Hyper+Z", "Z+Ctrl"] {
+        assert!(
+            matches!(
+                invalid.parse::<Chord>(),
+                Err(BindingsError::InvalidChord(_))
+            ),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn file_replaces_only_the_inputs_it_lists() {
+    let bindings =
+        Bindings::from_toml("Clear = [\"Back\", \"Ctrl+MouseRight\"]\nHose = []").unwrap();
+    assert_eq!(
+        bindings.chords(Input::Clear),
+        [chord("Back"), chord("Ctrl+MouseRight")]
+    );
+    assert!(bindings.chords(Input::Hose).is_empty());
+    assert_eq!(bindings.chords(Input::Save), [chord("S")]);
+}
+
+#[test]
+fn rejects_bad_files() {
+    assert!(matches!(
+        Bindings::from_toml("Clear = \"Space\""),
+        Err(BindingsError::Parse(_))
+    ));
+    assert!(matches!(
+        Bindings::from_toml("Jump = [\"Space\"]"),
+        Err(BindingsError::UnknownInput(_))
+    ));
+    assert!(matches!(
+        Bindings::from_toml("Clear = [\"Super+Space\"]"),
+        Err(BindingsError::InvalidChord(_))
+    ));
+    match Bindings::from_toml("Clear = [\"S\"]") {
+        Err(BindingsError::Conflict(chord, Input::Clear, Input::Save))
+        | Err(BindingsError::Conflict(chord, Input::Save, Input::Clear)) => {
+            assert_eq!(chord.to_string(), "S")
+        }
+        other => panic!("expected a conflict, got {:?}", other.err()),
+    }
+    // the same chord twice for one input is harmless
+    assert!(Bindings::from_toml("Clear = [\"Space\", \"Space\"]").is_ok());
+}
+
+#[test]
+fn the_most_specific_chord_wins() {
+    let bindings = Bindings::from_toml("Load = [\"Ctrl+S\"]").unwrap();
+    let s_down = |button: &Button| *button == key("S");
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Modifiers::default()
+    };
+    assert_eq!(
+        bindings.pressed(s_down, Modifiers::default()),
+        [Input::Save]
+    );
+    assert_eq!(bindings.pressed(s_down, ctrl), [Input::Load]);
+
+    // extra modifiers do not stop a chord with no rival on its button
+    let shift = Modifiers {
+        shift: true,
+        ..Modifiers::default()
+    };
+    let left_down = |button: &Button| *button == Button::Mouse(MouseButton::Left);
+    assert_eq!(bindings.pressed(left_down, shift), [Input::LeftMouse]);
+}
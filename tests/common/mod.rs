@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+use circles::{Circle, Colour, ContainerShape, Input, Inputs, SimConfig, State};
+use glam::{DVec2, IVec2};
+use std::ops::Range;
+
+/// One frame of a scripted session: clicks at a spread of points during the first
+/// 300 frames, occasionally deleting and clearing, then lets everything settle.
+/// Frame times vary to exercise the accumulator.
+pub fn script(frame: u32) -> (f64, Vec<Input>, IVec2) {
+    let mut held = Vec::new();
+    if frame.is_multiple_of(4) && frame < 300 {
+        held.push(Input::LeftMouse);
+    }
+    if frame.is_multiple_of(97) {
+        held.push(Input::RightMouse);
+    }
+    if frame == 200 {
+        held.push(Input::Clear);
+    }
+    let mouse = IVec2::new(
+        200 + (frame * 37 % 400) as i32,
+        250 + (frame * 11 % 200) as i32,
+    );
+    let dt = [1. / 60., 1. / 144., 1. / 30.][frame as usize % 3];
+    (dt, held, mouse)
+}
+
+/// Feeds `state` the scripted session from `frames`.
+pub fn drive(state: &mut State, inputs: &mut Inputs, frames: Range<u32>) {
+    for frame in frames {
+        let (dt, held, mouse) = script(frame);
+        inputs.update(held, mouse);
+        state.update(dt, inputs);
+    }
+}
+
+pub fn run(seed: u64, frames: u32) -> State {
+    let mut state = State::new(SimConfig::default(), seed);
+    drive(&mut state, &mut Inputs::new(), 0..frames);
+    state
+}
+
+/// The bit patterns of every circle's position and radius, plus its colour.
+pub type Fingerprint = Vec<(u64, u64, u64, Colour)>;
+
+pub fn fingerprint(state: &State) -> Fingerprint {
+    state
+        .world()
+        .circles()
+        .iter()
+        .map(|circle| {
+            let position = circle.position();
+            (
+                position.x.to_bits(),
+                position.y.to_bits(),
+                circle.radius().to_bits(),
+                circle.colour(),
+            )
+        })
+        .collect()
+}
+
+/// A box 300 wide and 600 tall, for circles to settle in.
+pub fn tall_box() -> ContainerShape {
+    ContainerShape::Rect {
+        width: 300.,
+        height: 600.,
+    }
+}
+
+/// Staggered rows of `columns` circles `spacing` apart, filling `tall_box`
+/// around `centre` from the top, top row first. The radii vary from `radius`
+/// to nine steps of `jitter` more.
+pub fn staggered_rows(
+    centre: DVec2,
+    rows: usize,
+    columns: usize,
+    spacing: f64,
+    radius: f64,
+    jitter: f64,
+) -> Vec<Circle> {
+    let stagger = spacing / 4.;
+    let width = (columns - 1) as f64 * spacing + stagger;
+    let origin = centre - DVec2::new(width / 2., 280.);
+    let mut circles = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let radius = radius + ((row * columns + column) * 37 % 10) as f64 * jitter;
+            let offset = DVec2::new(
+                column as f64 * spacing + (row % 2) as f64 * stagger,
+                row as f64 * spacing,
+            );
+            circles.push(Circle::new(origin + offset, radius, (255, 255, 255)));
+        }
+    }
+    circles
+}
+
+/// The mean height of the centres of `circles`, which grows downwards.
+pub fn mean_height(circles: &[Circle]) -> f64 {
+    circles
+        .iter()
+        .map(|circle| circle.position().y)
+        .sum::<f64>()
+        / circles.len() as f64
+}
@@ -0,0 +1,76 @@
+use circles::{ConfigError, SimConfig};
+
+fn parse(source: &str, overrides: &[&str]) -> Result<SimConfig, ConfigError> {
+    let overrides: Vec<String> = overrides.iter().map(|s| s.to_string()).collect();
+    SimConfig::from_toml(source, &overrides)
+}
+
+#[test]
+fn empty_config_is_default() {
+    assert_eq!(parse("", &[]).unwrap(), SimConfig::default());
+}
+
+#[test]
+fn file_and_overrides() {
+    let config = parse(
+        "gravity = 100.0\ntps = 60\nbackground = [10, 20, 30]",
+        &["gravity=250", "outer_colour = [1, 2, 3]"],
+    )
+    .unwrap();
+
+    assert_eq!(config.gravity, 250.);
+    assert_eq!(config.tps, 60);
+    assert_eq!(config.background, (10, 20, 30));
+    assert_eq!(config.outer_colour, (1, 2, 3));
+}
+
+#[test]
+fn rejects_nonsense() {
+    let invalid = [
+        "smallest_radius = 40.0",
+        "container = { shape = \"disc\", radius = 500.0 }",
+        "container = { shape = \"disc\", radius = 20.0 }",
+        "container = { shape = \"rect\", width = 100.0, height = 50.0 }",
+        "container = { shape = \"capsule\", length = -1.0, radius = 100.0 }",
+        "container = { shape = \"polygon\", vertices = [[0.0, 0.0], [100.0, 0.0]] }",
+        "container = { shape = \"polygon\", vertices = [[-100.0, -100.0], [100.0, -100.0], [0.0, -90.0], [0.0, 100.0]] }",
+        "tps = 0",
+        "sleep_ticks = 0",
+        "repetitions = 0",
+        "smallest_radius = -1.0",
+        "width = 0.0",
+        "gravity = nan",
+        "hose_rate = 1e12",
+        "hose_velocity = [1e9, 1e9]",
+        "emitters = [{ position = [400.0, 150.0], speed = 1e9 }]",
+        "container = { shape = \"polygon\", vertices = [[0.0, -300.0], [176.4, 242.7], [-285.3, -92.7], [285.3, -92.7], [-176.4, 242.7]] }",
+        "obstacles = [{ kind = \"polygon\", vertices = [[0.0, -100.0], [58.8, 80.9], [-95.1, -30.9], [95.1, -30.9], [-58.8, 80.9]] }]",
+        "emitters = [{ position = [400.0, 150.0], rate = 1e12 }]",
+    ];
+    for source in invalid {
+        assert!(
+            matches!(parse(source, &[]), Err(ConfigError::Invalid(_))),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn rejects_malformed() {
+    assert!(matches!(
+        parse("gravty = 1.0", &[]),
+        Err(ConfigError::Parse(_))
+    ));
+    assert!(matches!(
+        parse("tps = \"fast\"", &[]),
+        Err(ConfigError::Parse(_))
+    ));
+    assert!(matches!(
+        parse("", &["gravity"]),
+        Err(ConfigError::Override(_))
+    ));
+    assert!(matches!(
+        parse("", &["gravity=="]),
+        Err(ConfigError::Override(_))
+    ));
+}
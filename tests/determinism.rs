@@ -0,0 +1,20 @@
+mod common;
+
+use common::{fingerprint, run};
+
+#[test]
+fn same_seed_is_bit_identical() {
+    let a = run(42, 600);
+    let b = run(42, 600);
+
+    assert!(!a.world().circles().is_empty());
+    assert_eq!(fingerprint(&a), fingerprint(&b));
+}
+
+#[test]
+fn different_seeds_differ() {
+    let a = run(1, 600);
+    let b = run(2, 600);
+
+    assert_ne!(fingerprint(&a), fingerprint(&b));
+}
@@ -0,0 +1,389 @@
+use circles::{ContainerShape, Input, Inputs, Obstacle, SimConfig, State};
+use glam::{DVec2, IVec2};
+
+const DT: f64 = 1. / 64.;
+
+#[test]
+fn grabbed_circles_follow_the_cursor_and_can_be_thrown() {
+    let config = SimConfig {
+        gravity: 0.,
+        ..SimConfig::default()
+    };
+    let mut state = State::new(config, 0);
+    let mut inputs = Inputs::new();
+
+    inputs.update([Input::LeftMouse], IVec2::new(400, 400));
+    state.update(DT, &inputs);
+    inputs.update([], IVec2::new(400, 400));
+    state.update(DT, &inputs);
+    assert_eq!(state.world().circles().len(), 1);
+
+    // grab it slightly off centre and drag it right
+    let mut x = 402;
+    inputs.update([Input::LeftMouse], IVec2::new(x, 400));
+    state.update(DT, &inputs);
+    for _ in 0..10 {
+        x += 10;
+        inputs.update([Input::LeftMouse], IVec2::new(x, 400));
+        state.update(DT, &inputs);
+    }
+    let circle = &state.world().circles()[0];
+    assert_eq!(circle.position().x, (x - 2) as f64);
+    assert!(circle.pinned());
+
+    inputs.update([], IVec2::new(x, 400));
+    state.update(DT, &inputs);
+    let circle = &state.world().circles()[0];
+    assert!(!circle.pinned());
+    // 10 pixels per frame of two ticks
+    let velocity = circle.position() - circle.last_position();
+    assert!((velocity.x - 5.).abs() < 1e-9, "{velocity}");
+    assert!(circle.position().x > (x - 2) as f64);
+}
+
+#[test]
+fn hose_spawns_while_held() {
+    let config = SimConfig {
+        hose_rate: 16.,
+        hose_velocity: DVec2::new(0., 1000.),
+        largest_radius: 5.,
+        ..SimConfig::default()
+    };
+    let mut state = State::new(config, 0);
+    let mut inputs = Inputs::new();
+
+    inputs.update([Input::Hose], IVec2::new(400, 200));
+    state.update(DT, &inputs);
+    assert!(state.hose());
+    // one circle on the click, then one every four frames for a second
+    for _ in 0..64 {
+        inputs.update([Input::LeftMouse], IVec2::new(400, 200));
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.world().circles().len(), 1 + 15);
+    assert!(state.world().circles()[0].position().y > 300.);
+
+    // a blocked nozzle spawns nothing
+    let mut state = State::new(SimConfig::default(), 0);
+    inputs.update([Input::Hose], IVec2::new(0, 0));
+    state.update(DT, &inputs);
+    for _ in 0..64 {
+        inputs.update([Input::LeftMouse], IVec2::new(0, 0));
+        state.update(DT, &inputs);
+    }
+    assert!(state.world().circles().is_empty());
+}
+
+#[test]
+fn emitters_spawn_up_to_their_limit() {
+    let config = SimConfig::from_toml(
+        r#"
+        [[emitters]]
+        position = [400.0, 200.0]
+        rate = 32.0
+        speed = 400.0
+        radius = { distribution = "fixed", radius = 5.0 }
+        colour = { rule = "fixed", colour = [1, 2, 3] }
+        limit = 20
+        "#,
+        &[],
+    )
+    .unwrap();
+    let mut state = State::new(config, 0);
+    let mut inputs = Inputs::new();
+    for _ in 0..32 {
+        state.update(DT, &inputs);
+    }
+    // half a second at 32 per second
+    assert_eq!(state.world().circles().len(), 16);
+    assert!(state
+        .world()
+        .circles()
+        .iter()
+        .all(|circle| circle.radius() == 5. && circle.colour() == (1, 2, 3)));
+    for _ in 0..64 {
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.world().circles().len(), 20);
+
+    // pressing the key on the emitter removes it, and elsewhere places one
+    inputs.update([Input::Emitter], IVec2::new(405, 200));
+    state.update(DT, &inputs);
+    assert!(state.world().emitters().is_empty());
+    inputs.update([], IVec2::new(405, 200));
+    state.update(DT, &inputs);
+    inputs.update([Input::Emitter], IVec2::new(300, 300));
+    state.update(DT, &inputs);
+    assert_eq!(state.world().emitters().len(), 1);
+}
+
+#[test]
+fn long_frames_drop_time_instead_of_catching_up() {
+    let config = SimConfig {
+        max_ticks_per_frame: 8,
+        ..SimConfig::default()
+    };
+    let mut state = State::new(config, 0);
+    let inputs = Inputs::new();
+
+    state.update(DT, &inputs);
+    assert_eq!(state.ticks(), 2);
+    assert!(!state.slowed_down());
+
+    // a frame of over a quarter of an hour still runs only a handful of ticks
+    state.update(1000., &inputs);
+    assert_eq!(state.ticks(), 2 + 8);
+    assert!(state.slowed_down());
+    assert!((0. ..1.).contains(&state.interpolation()));
+
+    // and the notice clears once frames keep up again
+    for _ in 0..64 {
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.ticks(), 2 + 8 + 128);
+    assert!(!state.slowed_down());
+}
+
+#[test]
+fn pausing_stops_time_until_stepped() {
+    let mut state = State::new(SimConfig::default(), 0);
+    let mut inputs = Inputs::new();
+
+    inputs.update([Input::Pause], IVec2::ZERO);
+    state.update(DT, &inputs);
+    assert!(state.paused());
+    for _ in 0..10 {
+        inputs.update([], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.ticks(), 0);
+
+    // a step is one tick however long it is held
+    for _ in 0..10 {
+        inputs.update([Input::Step], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.ticks(), 1);
+
+    inputs.update([Input::Pause], IVec2::ZERO);
+    state.update(DT, &inputs);
+    assert!(!state.paused());
+    assert_eq!(state.ticks(), 3);
+}
+
+#[test]
+fn time_scale_changes_ticks_per_second() {
+    let mut state = State::new(SimConfig::default(), 0);
+    let mut inputs = Inputs::new();
+
+    inputs.update([Input::Faster], IVec2::ZERO);
+    state.update(DT, &inputs);
+    assert_eq!(state.time_scale(), 2.);
+    for _ in 1..64 {
+        inputs.update([], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.ticks(), 256);
+
+    for _ in 0..10 {
+        inputs.update([Input::Slower], IVec2::ZERO);
+        state.update(DT, &inputs);
+        inputs.update([], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.time_scale(), 0.1);
+}
+
+#[test]
+fn reversing_returns_to_earlier_states_exactly() {
+    let mut state = State::new(SimConfig::default(), 0);
+    let mut inputs = Inputs::new();
+    for frame in 0..32 {
+        let held = if frame % 8 == 0 {
+            vec![Input::LeftMouse]
+        } else {
+            vec![]
+        };
+        inputs.update(held, IVec2::new(300 + frame * 10, 300));
+        state.update(DT, &inputs);
+    }
+    let mut earlier = Vec::new();
+    state.save(&mut earlier).unwrap();
+    let ticks = state.ticks();
+
+    for _ in 0..32 {
+        inputs.update([], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    inputs.update([Input::Reverse], IVec2::ZERO);
+    state.update(DT, &inputs);
+    while state.ticks() > ticks {
+        inputs.update([], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.ticks(), ticks);
+    assert!(state.reversed());
+
+    // running forwards again continues as if time had never gone backwards
+    let mut original = State::load(&earlier[..]).unwrap();
+    inputs.update([Input::Reverse], IVec2::ZERO);
+    state.update(DT, &inputs);
+    inputs.update([], IVec2::ZERO);
+    original.update(DT, &inputs);
+    for _ in 0..32 {
+        inputs.update([], IVec2::ZERO);
+        state.update(DT, &inputs);
+        original.update(DT, &inputs);
+    }
+    let (mut resumed, mut expected) = (Vec::new(), Vec::new());
+    state.save(&mut resumed).unwrap();
+    original.save(&mut expected).unwrap();
+    assert!(resumed == expected);
+}
+
+#[test]
+fn reversing_pauses_at_the_start_of_the_history() {
+    let config = SimConfig {
+        history: 0.25,
+        ..SimConfig::default()
+    };
+    let mut state = State::new(config, 0);
+    let mut inputs = Inputs::new();
+    for _ in 0..64 {
+        state.update(DT, &inputs);
+    }
+
+    inputs.update([Input::Reverse], IVec2::ZERO);
+    state.update(DT, &inputs);
+    for _ in 0..64 {
+        inputs.update([], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    assert_eq!(state.ticks(), 128 - 32);
+    assert!(state.paused());
+    assert!(!state.reversed());
+}
+
+/// Presses `input` for one frame, then lets it go for one.
+fn tap(state: &mut State, inputs: &mut Inputs, input: Input) {
+    inputs.update([input], IVec2::ZERO);
+    state.update(DT, inputs);
+    inputs.update([], IVec2::ZERO);
+    state.update(DT, inputs);
+}
+
+fn save(state: &State) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    state.save(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn scrubbing_moves_through_the_history_and_resumes_from_there() {
+    let mut state = State::new(SimConfig::default(), 0);
+    let mut inputs = Inputs::new();
+    for frame in 0..64 {
+        let held = if frame % 8 == 0 {
+            vec![Input::LeftMouse]
+        } else {
+            vec![]
+        };
+        inputs.update(held, IVec2::new(300 + frame * 5, 300));
+        state.update(DT, &inputs);
+    }
+    let present = save(&state);
+    assert_eq!(state.ticks(), 128);
+
+    // each tap is one state of four ticks back, and time stands still meanwhile
+    for _ in 0..8 {
+        tap(&mut state, &mut inputs, Input::ScrubBack);
+    }
+    assert!(state.paused());
+    assert_eq!(state.ticks(), 96);
+    assert_eq!(state.scrub_position(), Some(0.75));
+    let past = save(&state);
+
+    // forwards again, to the present at the most
+    for _ in 0..10 {
+        tap(&mut state, &mut inputs, Input::ScrubForward);
+    }
+    assert!(save(&state) == present);
+
+    // holding keeps scrubbing
+    for _ in 0..8 {
+        inputs.update([Input::ScrubBack], IVec2::ZERO);
+        state.update(DT, &inputs);
+    }
+    assert!(state.ticks() < 96);
+    inputs.update([], IVec2::ZERO);
+    state.update(DT, &inputs);
+    while state.ticks() < 96 {
+        tap(&mut state, &mut inputs, Input::ScrubForward);
+    }
+    assert!(save(&state) == past);
+
+    // carrying on from the past goes the same way as it did the first time
+    let mut original = State::load(&past[..]).unwrap();
+    inputs.update([Input::Pause], IVec2::ZERO);
+    state.update(DT, &inputs);
+    inputs.update([], IVec2::ZERO);
+    original.update(DT, &inputs);
+    for _ in 0..32 {
+        state.update(DT, &inputs);
+        original.update(DT, &inputs);
+    }
+    assert!(save(&state) == save(&original));
+    assert_eq!(state.scrub_position(), None);
+}
+
+#[test]
+fn next_container_cycles_through_the_shapes() {
+    let mut state = State::new(SimConfig::default(), 0);
+    let mut inputs = Inputs::new();
+    let mut shapes = Vec::new();
+    for _ in 0..4 {
+        tap(&mut state, &mut inputs, Input::NextContainer);
+        shapes.push(state.world().config().container.clone());
+    }
+
+    assert!(matches!(
+        shapes.as_slice(),
+        [
+            ContainerShape::Rect { .. },
+            ContainerShape::Capsule { .. },
+            ContainerShape::Polygon { .. },
+            ContainerShape::Disc { .. },
+        ]
+    ));
+    assert_eq!(shapes[3], SimConfig::default().container);
+}
+
+#[test]
+fn editor_draws_and_removes_segments() {
+    let mut state = State::new(SimConfig::default(), 0);
+    let mut inputs = Inputs::new();
+    tap(&mut state, &mut inputs, Input::Editor);
+    assert!(state.editing());
+
+    for x in (200..=400).step_by(50) {
+        inputs.update([Input::RightMouse], IVec2::new(x, 300));
+        state.update(DT, &inputs);
+    }
+    inputs.update([], IVec2::new(400, 300));
+    state.update(DT, &inputs);
+    assert_eq!(
+        state.world().obstacles(),
+        [Obstacle::Segment {
+            start: DVec2::new(200., 300.),
+            end: DVec2::new(400., 300.),
+        }]
+    );
+
+    // a click without dragging removes the obstacle under the cursor
+    inputs.update([Input::RightMouse], IVec2::new(300, 301));
+    state.update(DT, &inputs);
+    inputs.update([], IVec2::new(300, 301));
+    state.update(DT, &inputs);
+    assert!(state.world().obstacles().is_empty());
+    assert!(state.world().circles().is_empty());
+}
@@ -0,0 +1,94 @@
+use circles::{Circle, ContainerShape, Material, SimConfig, World};
+use glam::DVec2;
+
+const TICK: f64 = 1. / 128.;
+
+/// A 600 pixel square box, whose floor is at y = 700.
+fn world(gravity: f64, wall_material: Material) -> World {
+    World::new(SimConfig {
+        gravity,
+        container: ContainerShape::Rect {
+            width: 600.,
+            height: 600.,
+        },
+        wall_material,
+        ..SimConfig::default()
+    })
+}
+
+#[test]
+fn bounces_lose_restitution_squared_of_their_height() {
+    let material = Material {
+        restitution: 0.8,
+        friction: 0.,
+    };
+    let mut world = world(500., Material::default());
+    world.add(Circle::new(DVec2::new(400., 200.), 10., (255, 255, 255)).with_material(material));
+
+    let mut highest = f64::INFINITY;
+    let mut rising = false;
+    for _ in 0..1000 {
+        world.step();
+        let circle = &world.circles()[0];
+        if circle.velocity().y < 0. {
+            rising = true;
+            highest = highest.min(circle.position().y);
+        } else if rising {
+            break;
+        }
+    }
+
+    let drop = 690. - 200.;
+    let rebound = 690. - highest;
+    assert!((rebound / drop - 0.64).abs() < 0.02, "{rebound}");
+}
+
+#[test]
+fn friction_turns_sliding_into_rolling() {
+    let material = Material {
+        restitution: 0.,
+        friction: 0.3,
+    };
+    let mut world = world(500., material);
+    let circle = Circle::new(DVec2::new(200., 690.), 10., (255, 255, 255))
+        .with_material(material)
+        .with_velocity(DVec2::new(300. * TICK, 0.));
+    world.add(circle);
+
+    for _ in 0..128 {
+        world.step();
+    }
+
+    // a sliding disc slows to two thirds of its speed as it starts to roll, after
+    // v / 3μg seconds
+    let circle = &world.circles()[0];
+    let velocity = circle.velocity() / TICK;
+    assert!((velocity.x - 200.).abs() < 1., "{velocity}");
+    let contact = circle.surface_velocity(DVec2::Y) / TICK;
+    assert!(contact.length() < 1e-6, "{contact}");
+    let rolled = 300. * 2. / 3. - 150. * (2. / 3.) * (2. / 3.) / 2. + 200. / 3.;
+    assert!((circle.position().x - 200. - rolled).abs() < 5.);
+    // rolling right turns clockwise on screen
+    assert!((circle.angular_velocity() * 10. - circle.velocity().x).abs() < 1e-9);
+}
+
+#[test]
+fn elastic_collisions_swap_velocities() {
+    let material = Material {
+        restitution: 1.,
+        friction: 0.,
+    };
+    let mut world = world(0., Material::default());
+    let moving = Circle::new(DVec2::new(300., 400.), 10., (255, 255, 255))
+        .with_velocity(DVec2::new(200. * TICK, 0.));
+    world.add(moving.with_material(material));
+    world.add(Circle::new(DVec2::new(400., 400.), 10., (255, 255, 255)).with_material(material));
+
+    for _ in 0..64 {
+        world.step();
+    }
+
+    let circles = world.circles();
+    assert!(circles[0].velocity().length() < 1e-9);
+    assert!((circles[1].velocity().x / TICK - 200.).abs() < 1e-6);
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use circles::{Circle, SimConfig, World};
+use common::{mean_height, staggered_rows, tall_box};
+use glam::DVec2;
+
+/// Circles of varied sizes dropped into a box and left to settle.
+fn settle(parallel: bool) -> World {
+    let config = SimConfig {
+        container: tall_box(),
+        parallel,
+        ..SimConfig::default()
+    };
+    let mut world = World::new(config);
+    for circle in staggered_rows(world.centre(), 12, 13, 21., 6., 0.4) {
+        world.add(circle);
+    }
+    for _ in 0..1500 {
+        world.step();
+    }
+    world
+}
+
+fn deepest_overlap(world: &World) -> f64 {
+    let circles = world.circles();
+    let mut deepest = 0_f64;
+    for (i, a) in circles.iter().enumerate() {
+        for b in &circles[i + 1..] {
+            let overlap = a.radius() + b.radius() - a.position().distance(b.position());
+            deepest = deepest.max(overlap);
+        }
+    }
+    deepest
+}
+
+#[test]
+fn parallel_and_serial_solvers_settle_alike() {
+    let serial = settle(false);
+    let parallel = settle(true);
+
+    // the piles differ circle by circle, but are packed as tightly and as deep
+    assert!(deepest_overlap(&serial) < 0.5);
+    assert!(deepest_overlap(&parallel) < 0.5);
+    let difference = mean_height(serial.circles()) - mean_height(parallel.circles());
+    assert!(difference.abs() < 1., "{difference}");
+    assert!(parallel.circles().iter().all(|circle| circle.asleep()));
+}
+
+#[test]
+fn parallel_solving_does_not_depend_on_threads() {
+    let parallel = settle(true);
+    let one_thread = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap()
+        .install(|| settle(true));
+
+    let positions =
+        |world: &World| -> Vec<DVec2> { world.circles().iter().map(Circle::position).collect() };
+    assert_eq!(positions(&parallel), positions(&one_thread));
+}
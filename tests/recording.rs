@@ -0,0 +1,81 @@
+mod common;
+
+use circles::{Inputs, Recorder, Recording, SimConfig, State};
+use common::{fingerprint, run, script};
+
+fn record(seed: u64, frames: u32) -> (State, Recording) {
+    let mut state = State::new(SimConfig::default(), seed);
+    let mut inputs = Inputs::new();
+    let mut recording = Recording::new(&state, &inputs);
+
+    for frame in 0..frames {
+        let (dt, held, mouse) = script(frame);
+        inputs.update(held, mouse);
+        recording.record(dt, &inputs);
+        state.update(dt, &inputs);
+    }
+
+    (state, recording)
+}
+
+#[test]
+fn replay_reproduces_session() {
+    let (state, recording) = record(3, 500);
+
+    assert_eq!(recording.frames().len(), 500);
+    assert_eq!(fingerprint(&recording.replay()), fingerprint(&state));
+}
+
+#[test]
+fn replay_survives_save_and_load() {
+    let (state, recording) = record(3, 500);
+
+    let mut buffer = Vec::new();
+    recording.save(&mut buffer).unwrap();
+    let loaded = Recording::load(buffer.as_slice()).unwrap();
+
+    assert_eq!(fingerprint(&loaded.replay()), fingerprint(&state));
+}
+
+#[test]
+fn recording_can_start_midway() {
+    let mut state = run(3, 100);
+    let inputs = Inputs::new();
+    let recording = Recording::new(&state, &inputs);
+
+    assert_eq!(fingerprint(&recording.replay()), fingerprint(&state));
+
+    state.update(1., &inputs);
+    assert_ne!(fingerprint(&recording.replay()), fingerprint(&state));
+}
+
+#[test]
+fn recorder_writes_what_recording_saves() {
+    let (_, recording) = record(3, 200);
+    let mut saved = Vec::new();
+    recording.save(&mut saved).unwrap();
+
+    let mut written = Vec::new();
+    let (state, mut inputs) = recording.start();
+    let mut recorder = Recorder::new(&mut written, &state, &inputs).unwrap();
+    for frame in recording.frames() {
+        frame.apply(&mut inputs);
+        recorder.record(frame.dt, &inputs).unwrap();
+    }
+
+    assert!(written == saved);
+}
+
+#[test]
+fn recordings_cut_short_keep_their_complete_frames() {
+    let (_, recording) = record(3, 200);
+    let mut buffer = Vec::new();
+    recording.save(&mut buffer).unwrap();
+    // a crash partway through writing the last frame
+    buffer.truncate(buffer.len() - 5);
+
+    let loaded = Recording::load(buffer.as_slice()).unwrap();
+    assert_eq!(loaded.frames().len(), 199);
+    let (state, _) = record(3, 199);
+    assert_eq!(fingerprint(&loaded.replay()), fingerprint(&state));
+}
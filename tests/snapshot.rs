@@ -0,0 +1,145 @@
+mod common;
+
+use circles::{Inputs, SnapshotError, State};
+use common::{drive, fingerprint, run};
+
+fn round_trip(state: &State) -> State {
+    let mut buffer = Vec::new();
+    state.save(&mut buffer).unwrap();
+    State::load(buffer.as_slice()).unwrap()
+}
+
+#[test]
+fn round_trip_preserves_state() {
+    let state = run(7, 400);
+    let loaded = round_trip(&state);
+
+    assert!(!state.world().circles().is_empty());
+    assert_eq!(fingerprint(&state), fingerprint(&loaded));
+    assert_eq!(state.seed(), loaded.seed());
+    assert_eq!(
+        state.interpolation().to_bits(),
+        loaded.interpolation().to_bits()
+    );
+    assert_eq!(state.world().config(), loaded.world().config());
+    for (a, b) in state.world().circles().iter().zip(loaded.world().circles()) {
+        assert_eq!(a.last_position(), b.last_position());
+    }
+}
+
+#[test]
+fn loaded_state_continues_identically() {
+    // save midway through spawning, so the RNG state matters
+    let mut original = run(7, 150);
+    let mut loaded = round_trip(&original);
+
+    let mut inputs = Inputs::new();
+    drive(&mut original, &mut inputs, 150..600);
+    let mut inputs = Inputs::new();
+    drive(&mut loaded, &mut inputs, 150..600);
+
+    assert_eq!(fingerprint(&original), fingerprint(&loaded));
+}
+
+#[test]
+fn rejects_other_versions() {
+    let mut buffer = Vec::new();
+    run(7, 10).save(&mut buffer).unwrap();
+    let mut json: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    json["version"] = 999.into();
+    let buffer = serde_json::to_vec(&json).unwrap();
+
+    assert!(matches!(
+        State::load(buffer.as_slice()),
+        Err(SnapshotError::UnsupportedVersion(999))
+    ));
+}
+
+#[test]
+fn rejects_malformed_snapshots() {
+    let mut buffer = Vec::new();
+    run(7, 10).save(&mut buffer).unwrap();
+    let mut json: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    json.as_object_mut().unwrap().remove("circles");
+    let buffer = serde_json::to_vec(&json).unwrap();
+
+    assert!(matches!(
+        State::load(buffer.as_slice()),
+        Err(SnapshotError::Format(_))
+    ));
+    assert!(matches!(
+        State::load(&b"[]"[..]),
+        Err(SnapshotError::MissingVersion)
+    ));
+}
+
+#[test]
+fn rejects_invalid_circles() {
+    let mut buffer = Vec::new();
+    run(7, 100).save(&mut buffer).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+
+    let invalid = [
+        ("radius", serde_json::json!(-5.0)),
+        ("radius", serde_json::json!(0.0)),
+        ("position", serde_json::json!([3e9, 3e9])),
+        ("last_position", serde_json::json!([400.0, -1.0])),
+    ];
+    for (key, value) in invalid {
+        let mut json = json.clone();
+        json["circles"][0][key] = value.clone();
+        let buffer = serde_json::to_vec(&json).unwrap();
+        assert!(
+            matches!(
+                State::load(buffer.as_slice()),
+                Err(SnapshotError::InvalidCircle(0))
+            ),
+            "{key} = {value}"
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_links() {
+    let mut buffer = Vec::new();
+    run(7, 100).save(&mut buffer).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    let with_link = |a: usize, b: usize, kind: serde_json::Value| {
+        let mut json = json.clone();
+        json["links"] = serde_json::json!([{ "a": a, "b": b, "kind": kind }]);
+        serde_json::to_vec(&json).unwrap()
+    };
+
+    let rope = serde_json::json!({ "type": "range", "min": 0.0, "max": 50.0 });
+    assert!(State::load(with_link(0, 1, rope.clone()).as_slice()).is_ok());
+
+    let invalid = [
+        (0, 0, rope.clone()),
+        (0, 1000, rope),
+        (
+            0,
+            1,
+            serde_json::json!({ "type": "range", "min": 10.0, "max": 5.0 }),
+        ),
+        (0, 1, serde_json::json!({ "type": "rigid", "length": -1.0 })),
+        (
+            0,
+            1,
+            serde_json::json!({ "type": "spring", "length": 50.0, "stiffness": 0.0 }),
+        ),
+        (
+            0,
+            1,
+            serde_json::json!({ "type": "spring", "length": 50.0, "stiffness": 1.5 }),
+        ),
+    ];
+    for (a, b, kind) in invalid {
+        assert!(
+            matches!(
+                State::load(with_link(a, b, kind.clone()).as_slice()),
+                Err(SnapshotError::InvalidLink(_))
+            ),
+            "{a} {b} {kind}"
+        );
+    }
+}
@@ -0,0 +1,472 @@
+mod common;
+
+use circles::{BroadPhase, Circle, ContainerShape, Link, LinkKind, Obstacle, SimConfig, World};
+use common::{mean_height, staggered_rows, tall_box};
+use glam::DVec2;
+
+#[test]
+fn pinned_circles_hold_their_place() {
+    let mut world = World::new(SimConfig::default());
+    let centre = world.centre();
+    world.add(Circle::new(centre, 20., (255, 255, 255)));
+    world.set_pinned(0, true);
+    // a stack resting on the pinned circle, and one hanging below it
+    for i in 1..=5 {
+        let position = centre - DVec2::new(0.5, 40. * i as f64);
+        world.add(Circle::new(position, 20., (255, 255, 255)));
+    }
+    world.add(Circle::new(
+        centre + DVec2::new(0., 60.),
+        10.,
+        (255, 255, 255),
+    ));
+    world.add_link(Link {
+        a: 0,
+        b: 6,
+        kind: LinkKind::Rigid { length: 60. },
+    });
+
+    for _ in 0..1000 {
+        world.step();
+    }
+
+    let circles = world.circles();
+    assert_eq!(circles[0].position(), centre);
+    assert!((circles[6].position().distance(centre) - 60.).abs() < 0.01);
+}
+
+/// Two layers of circles with slightly varied sizes, the top one `density`
+/// times as dense as the bottom, left to settle. Returns the mean height of
+/// each layer, top first.
+fn settle_layers(density: f64) -> (f64, f64) {
+    let config = SimConfig {
+        container: tall_box(),
+        ..SimConfig::default()
+    };
+    let mut world = World::new(config);
+    let circles = staggered_rows(world.centre(), 12, 7, 40., 11., 0.8);
+    for (i, circle) in circles.into_iter().enumerate() {
+        let density = if i < 42 { density } else { 1. };
+        world.add(circle.with_density(density));
+    }
+
+    for _ in 0..2000 {
+        world.step();
+    }
+
+    let (top, bottom) = world.circles().split_at(42);
+    (mean_height(top), mean_height(bottom))
+}
+
+#[test]
+fn dense_circles_sink() {
+    // y increases downwards
+    let (top, bottom) = settle_layers(1.);
+    assert!(top < bottom);
+    let (top, bottom) = settle_layers(50.);
+    assert!(top > bottom);
+}
+
+/// The speed of a circle falling for four seconds in a tall box.
+fn fall(linear_drag: f64, quadratic_drag: f64) -> f64 {
+    let mut world = World::new(SimConfig {
+        gravity: 100.,
+        linear_drag,
+        quadratic_drag,
+        container: ContainerShape::Rect {
+            width: 100.,
+            height: 700.,
+        },
+        ..SimConfig::default()
+    });
+    world.add(Circle::new(DVec2::new(400., 60.), 5., (255, 255, 255)));
+    for _ in 0..512 {
+        world.step();
+    }
+    world.circles()[0].velocity().y * world.config().tps as f64
+}
+
+#[test]
+fn drag_limits_falling_speed() {
+    // g / k and √(g / c)
+    assert!((fall(2., 0.) - 50.).abs() < 1.);
+    assert!((fall(0., 0.01) - 100.).abs() < 1.);
+}
+
+#[test]
+fn settled_piles_sleep_until_disturbed() {
+    let mut world = World::new(SimConfig::default());
+    let origin = world.centre() + DVec2::new(-90., 100.);
+    for row in 0..10 {
+        for column in 0..10 {
+            let radius = 8. + (row * 10 + column) as f64 % 3.;
+            let offset = DVec2::new(
+                column as f64 * 20. + (row % 2) as f64 * 5.,
+                row as f64 * 20.,
+            );
+            world.add(Circle::new(origin + offset, radius, (255, 255, 255)));
+        }
+    }
+
+    let mut steps = 0;
+    while !world.circles().iter().all(Circle::asleep) {
+        world.step();
+        steps += 1;
+        assert!(steps < 5000, "the pile never fell asleep");
+    }
+    let settled: Vec<_> = world.circles().iter().map(Circle::position).collect();
+    for _ in 0..100 {
+        world.step();
+    }
+    let positions: Vec<_> = world.circles().iter().map(Circle::position).collect();
+    assert_eq!(positions, settled);
+
+    // a circle dropped on top wakes the pile
+    let top = settled
+        .iter()
+        .copied()
+        .reduce(|a, b| if a.y < b.y { a } else { b })
+        .unwrap();
+    world.add(Circle::new(top - DVec2::new(0., 40.), 10., (255, 255, 255)));
+    for _ in 0..64 {
+        world.step();
+    }
+    let awake = world
+        .circles()
+        .iter()
+        .filter(|circle| !circle.asleep())
+        .count();
+    assert!(awake > 50, "{awake}");
+}
+
+/// Where a small circle fired down at 3000 pixels per second ends up after
+/// meeting a horizontal segment.
+fn fire_at_segment(adaptive: bool) -> DVec2 {
+    let mut world = World::new(SimConfig {
+        gravity: 0.,
+        adaptive,
+        obstacles: vec![Obstacle::Segment {
+            start: DVec2::new(300., 400.),
+            end: DVec2::new(500., 400.),
+        }],
+        ..SimConfig::default()
+    });
+    let tick = world.config().tick_duration();
+    let circle = Circle::new(DVec2::new(400., 300.), 5., (255, 255, 255))
+        .with_velocity(DVec2::new(0., 3000. * tick));
+    world.add(circle);
+    for _ in 0..16 {
+        world.step();
+    }
+    world.circles()[0].position()
+}
+
+#[test]
+fn substeps_stop_fast_circles_tunnelling() {
+    assert!(fire_at_segment(false).y > 400.);
+    assert!(fire_at_segment(true).y < 400.);
+}
+
+#[test]
+fn more_iterations_leave_less_overlap() {
+    let overlap = |repetitions| {
+        let mut world = World::new(SimConfig {
+            repetitions,
+            sleep_speed: 0.,
+            ..SimConfig::default()
+        });
+        let origin = world.centre() + DVec2::new(-90., 100.);
+        for i in 0..100 {
+            let offset = DVec2::new((i % 10) as f64 * 20., (i / 10) as f64 * 20.);
+            world.add(Circle::new(origin + offset, 10., (255, 255, 255)));
+        }
+        for _ in 0..500 {
+            world.step();
+        }
+        world.overlap()
+    };
+    assert!(overlap(16) < overlap(4));
+    assert!(overlap(4) < overlap(1));
+}
+
+/// The positions after `ticks` of a lattice of circles of varied sizes, none
+/// overlapping at the start, falling into a pile.
+fn lattice(broad_phase: BroadPhase, ticks: u32) -> Vec<DVec2> {
+    let mut world = World::new(SimConfig::default());
+    world.set_broad_phase(broad_phase);
+    let centre = world.centre();
+    for y in -8..=8 {
+        for x in -8..=8 {
+            let position = DVec2::new(x as f64, y as f64) * 30.;
+            if position.length() < 250. {
+                let radius = 8. + (x * 7 + y * 13_i32).rem_euclid(10) as f64 * 0.6;
+                world.add(Circle::new(centre + position, radius, (255, 255, 255)));
+            }
+        }
+    }
+    for _ in 0..ticks {
+        world.step();
+    }
+    world.circles().iter().map(Circle::position).collect()
+}
+
+#[test]
+fn grid_broad_phase_matches_brute_force() {
+    assert_eq!(
+        lattice(BroadPhase::Grid, 1000),
+        lattice(BroadPhase::BruteForce, 1000)
+    );
+}
+
+#[test]
+fn circles_stay_inside_every_container() {
+    for shape in ContainerShape::presets(350.) {
+        let mut world = World::new(SimConfig::default());
+        world.set_container(shape.clone());
+        let centre = world.centre();
+        // a block of circles thrown outwards in every direction
+        for y in -4..=4_i32 {
+            for x in -6..=6 {
+                let offset = DVec2::new(x as f64, y as f64) * 24.;
+                let radius = 6. + (x + y).rem_euclid(4) as f64 * 1.5;
+                let circle = Circle::new(centre + offset, radius, (255, 255, 255));
+                world.add(circle.with_velocity(offset * 20.));
+            }
+        }
+
+        for _ in 0..600 {
+            world.step();
+        }
+
+        let container = world.container();
+        for circle in world.circles() {
+            let distance = container.distance(circle.position());
+            assert!(
+                distance >= circle.radius() - 1e-6,
+                "{shape:?}: circle at {} is {distance} from the wall",
+                circle.position()
+            );
+        }
+    }
+}
+
+/// A world with no gravity holding `obstacle`, or with the usual gravity if
+/// `falling`.
+fn with_obstacle(obstacle: Obstacle, falling: bool) -> World {
+    let config = SimConfig {
+        gravity: if falling { 500. } else { 0. },
+        ..SimConfig::default()
+    };
+    let mut world = World::new(config);
+    world.add_obstacle(obstacle);
+    world
+}
+
+#[test]
+fn circles_rest_on_segments() {
+    let centre = SimConfig::default().centre();
+    let mut world = with_obstacle(
+        Obstacle::Segment {
+            start: centre + DVec2::new(-200., 100.),
+            end: centre + DVec2::new(200., 100.),
+        },
+        true,
+    );
+    world.add(Circle::new(centre, 20., (255, 255, 255)));
+
+    for _ in 0..600 {
+        world.step();
+    }
+
+    let circle = &world.circles()[0];
+    assert!((circle.position().y - (centre.y + 80.)).abs() < 0.5);
+    assert_eq!(circle.position().x, centre.x);
+}
+
+#[test]
+fn circles_balance_on_polyline_vertices() {
+    let centre = SimConfig::default().centre();
+    let peak = centre + DVec2::new(0., 50.);
+    let mut world = with_obstacle(
+        Obstacle::Polyline {
+            points: vec![
+                centre + DVec2::new(-100., 150.),
+                peak,
+                centre + DVec2::new(100., 150.),
+            ],
+        },
+        true,
+    );
+    world.add(Circle::new(centre, 20., (255, 255, 255)));
+
+    for _ in 0..600 {
+        world.step();
+    }
+
+    let circle = &world.circles()[0];
+    assert!((circle.position().distance(peak) - 20.).abs() < 0.5);
+    assert_eq!(circle.position().x, peak.x);
+}
+
+#[test]
+fn circles_are_pushed_out_of_polygons_and_circles() {
+    let centre = SimConfig::default().centre();
+    let obstacles = [
+        Obstacle::Polygon {
+            vertices: vec![
+                centre + DVec2::new(-50., -50.),
+                centre + DVec2::new(50., -50.),
+                centre + DVec2::new(0., 50.),
+            ],
+        },
+        Obstacle::Circle {
+            centre,
+            radius: 40.,
+        },
+    ];
+    for obstacle in obstacles {
+        let mut world = with_obstacle(obstacle.clone(), false);
+        // one circle overlapping the edge and one centred inside
+        world.add(Circle::new(
+            centre + DVec2::new(5., -45.),
+            10.,
+            (255, 255, 255),
+        ));
+        world.add(Circle::new(
+            centre + DVec2::new(3., 1.),
+            10.,
+            (255, 255, 255),
+        ));
+
+        world.step();
+
+        for circle in world.circles() {
+            let distance = obstacle.distance(circle.position());
+            assert!(
+                distance >= circle.radius() - 1e-6,
+                "{obstacle:?}: {distance}"
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "link range")]
+fn backwards_ranges_are_rejected() {
+    let mut world = World::new(SimConfig::default());
+    let centre = world.centre();
+    world.add(Circle::new(centre, 10., (255, 255, 255)));
+    world.add(Circle::new(centre + DVec2::X * 30., 10., (255, 255, 255)));
+    world.add_link(Link {
+        a: 0,
+        b: 1,
+        kind: LinkKind::Range { min: 10., max: 5. },
+    });
+}
+
+#[test]
+fn far_flung_circles_do_not_break_the_broad_phase() {
+    let mut world = World::new(SimConfig::default());
+    let centre = world.centre();
+    world.add(Circle::new(centre, 10., (255, 255, 255)));
+    world.add(Circle::new(centre + DVec2::X * 25., 10., (255, 255, 255)));
+    // thrown so hard it lands billions of cells away before the walls catch it
+    let thrown = Circle::new(centre - DVec2::X * 25., 10., (255, 255, 255));
+    world.add(thrown.with_velocity(DVec2::splat(1e9)));
+
+    for _ in 0..10 {
+        world.step();
+    }
+
+    for circle in world.circles() {
+        assert!(world.container().distance(circle.position()) >= circle.radius() - 1e-6);
+    }
+}
+
+#[test]
+fn range_links_allow_slack_but_never_stretch_past_max() {
+    let mut world = World::new(SimConfig::default());
+    let centre = world.centre();
+    world.add(Circle::new(centre, 10., (255, 255, 255)));
+    world.set_pinned(0, true);
+    world.add(Circle::new(centre + DVec2::Y * 40., 10., (255, 255, 255)));
+    world.add_link(Link {
+        a: 0,
+        b: 1,
+        kind: LinkKind::Range { min: 0., max: 80. },
+    });
+
+    let mut distances = Vec::new();
+    for _ in 0..200 {
+        world.step();
+        let circles = world.circles();
+        distances.push(circles[0].position().distance(circles[1].position()));
+    }
+
+    // it falls freely through the slack, then hangs at the full length
+    assert!(distances[1] > 40. && distances[1] < 80.);
+    assert!(distances.iter().all(|&distance| distance <= 80. + 1e-6));
+    assert!((distances.last().unwrap() - 80.).abs() < 1e-6);
+}
+
+#[test]
+fn springs_settle_at_their_length() {
+    let config = SimConfig {
+        gravity: 0.,
+        ..SimConfig::default()
+    };
+    let mut world = World::new(config);
+    let centre = world.centre();
+    world.add(Circle::new(centre - DVec2::X * 50., 10., (255, 255, 255)));
+    world.add(Circle::new(centre + DVec2::X * 50., 10., (255, 255, 255)));
+    world.add_link(Link {
+        a: 0,
+        b: 1,
+        kind: LinkKind::Spring {
+            length: 50.,
+            stiffness: 0.1,
+        },
+    });
+
+    for _ in 0..2000 {
+        world.step();
+    }
+
+    let circles = world.circles();
+    let distance = circles[0].position().distance(circles[1].position());
+    assert!((distance - 50.).abs() < 0.01, "{distance}");
+}
+
+#[test]
+fn removing_a_circle_renumbers_the_links_of_the_last() {
+    let mut world = World::new(SimConfig::default());
+    let centre = world.centre();
+    for i in 0..4 {
+        let position = centre + DVec2::X * (i as f64 * 50. - 75.);
+        world.add(Circle::new(position, 10., (255, 255, 255)));
+    }
+    let rope = LinkKind::Range { min: 0., max: 60. };
+    world.add_link(Link {
+        a: 0,
+        b: 1,
+        kind: rope,
+    });
+    world.add_link(Link {
+        a: 2,
+        b: 3,
+        kind: rope,
+    });
+    let last = world.circles()[3].position();
+
+    world.remove(1);
+
+    assert_eq!(world.circles()[1].position(), last);
+    assert_eq!(
+        world.links(),
+        [Link {
+            a: 2,
+            b: 1,
+            kind: rope,
+        }]
+    );
+}